@@ -0,0 +1,276 @@
+mod render;
+
+use std::{io::Write, path::Path, process::ExitCode};
+
+use polygon_model::{
+    document::Document, geometry::Geometry, intersections::SelfIntersections, point::Point,
+    shape::Shape, Rect,
+};
+
+use render::Canvas;
+
+const USAGE: &str = "\
+Usage: polygon-cli <command> [options]
+
+Commands:
+  validate <file>             check that every constraint is kept and every shape is simple
+  convert <input> <output>    convert between formats chosen by extension (.json, .svg)
+  render <input> <output.png> draw outlines with the Bresenham algorithm
+  measure <file>              print vertices, lengths, areas and bounds of every shape
+
+Options:
+  --tolerance <pixels>        allowed error of constraints and of polylines replacing curves (default 1)
+  --scale <factor>            size of a document unit in the rendered image (default 1)
+
+Exit codes:
+  0  success, document is valid
+  1  document is invalid: shapes with too few points, broken constraints or self-intersections
+  2  wrong arguments
+  3  file can't be read, parsed or written";
+
+/// Failure of a command, each kind has its own exit code
+enum CliError {
+    Invalid,
+    Usage(String),
+    File(String),
+}
+
+impl CliError {
+    fn exit_code(&self) -> ExitCode {
+        match self {
+            CliError::Invalid => ExitCode::from(1),
+            CliError::Usage(_) => ExitCode::from(2),
+            CliError::File(_) => ExitCode::from(3),
+        }
+    }
+}
+
+struct Options {
+    tolerance: f32,
+    scale: f32,
+}
+
+fn main() -> ExitCode {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    match run(&args) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            match &e {
+                CliError::Invalid => {}
+                CliError::Usage(message) => eprintln!("{}\n\n{}", message, USAGE),
+                CliError::File(message) => eprintln!("{}", message),
+            }
+            e.exit_code()
+        }
+    }
+}
+
+fn run(args: &[String]) -> Result<(), CliError> {
+    let (positional, options) = parse_options(args)?;
+    match positional.as_slice() {
+        ["validate", file] => validate(file, &options),
+        ["convert", input, output] => convert(input, output),
+        ["render", input, output] => render(input, output, &options),
+        ["measure", file] => measure(file, &options),
+        ["help"] | ["--help"] | ["-h"] => {
+            print_lines(&[USAGE.to_string()]);
+            Ok(())
+        }
+        [] => Err(CliError::Usage("Missing command".to_string())),
+        [command, ..] => Err(CliError::Usage(format!(
+            "Unknown command or wrong number of arguments: {}",
+            command
+        ))),
+    }
+}
+
+/// Prints the lines to stdout, output closed early by the reader (like `head`) is not an error
+fn print_lines(lines: &[String]) {
+    let mut stdout = std::io::stdout().lock();
+    for line in lines {
+        if writeln!(stdout, "{}", line).is_err() {
+            return;
+        }
+    }
+}
+
+/// Splits arguments into positional ones and values of options
+fn parse_options(args: &[String]) -> Result<(Vec<&str>, Options), CliError> {
+    let mut positional = vec![];
+    let mut options = Options {
+        tolerance: 1.0,
+        scale: 1.0,
+    };
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        let option = match arg.as_str() {
+            "--tolerance" => &mut options.tolerance,
+            "--scale" => &mut options.scale,
+            _ => {
+                positional.push(arg.as_str());
+                continue;
+            }
+        };
+        *option = args
+            .next()
+            .and_then(|value| value.parse().ok())
+            .filter(|value: &f32| *value > 0.0)
+            .ok_or_else(|| CliError::Usage(format!("{} needs a positive number", arg)))?;
+    }
+    Ok((positional, options))
+}
+
+fn load(file: &str) -> Result<Vec<Shape>, CliError> {
+    Document::load(Path::new(file))
+        .map(Document::into_shapes)
+        .map_err(|e| CliError::File(format!("{}: {}", file, e)))
+}
+
+fn validate(file: &str, options: &Options) -> Result<(), CliError> {
+    let shapes = load(file)?;
+    let mut problems = vec![];
+    if shapes.is_empty() {
+        problems.push("document has no shapes".to_string());
+    }
+    for (shape_id, shape) in shapes.iter().enumerate() {
+        let shape_name = format!("shape {}", shape_id + 1);
+        if !shape.is_valid() {
+            problems.push(format!("{} has too few points", shape_name));
+            continue;
+        }
+        for (ring_id, ring) in std::iter::once(shape.outline())
+            .chain(shape.holes())
+            .enumerate()
+        {
+            let ring_name = match ring_id {
+                0 => shape_name.clone(),
+                _ => format!("{}, hole {}", shape_name, ring_id),
+            };
+            for edge_id in
+                Point::unsatisfied_constraints(ring, shape.is_closed(), options.tolerance)
+            {
+                let constraint = ring[edge_id]
+                    .constraint()
+                    .expect("Only constrained edges are reported");
+                problems.push(format!(
+                    "{}: edge {} breaks its {}",
+                    ring_name,
+                    edge_id + 1,
+                    constraint.name()
+                ));
+            }
+            let intersections = SelfIntersections::find(ring, shape.is_closed());
+            if !intersections.is_empty() {
+                problems.push(format!(
+                    "{}: is not simple, it has {} self-intersections",
+                    ring_name,
+                    intersections.len()
+                ));
+            }
+        }
+    }
+    if problems.is_empty() {
+        print_lines(&[format!("{}: valid, {} shapes", file, shapes.len())]);
+        Ok(())
+    } else {
+        let report: Vec<String> = problems
+            .iter()
+            .map(|problem| format!("{}: {}", file, problem))
+            .collect();
+        print_lines(&report);
+        Err(CliError::Invalid)
+    }
+}
+
+fn convert(input: &str, output: &str) -> Result<(), CliError> {
+    let shapes = load(input)?;
+    if let Some(id) = shapes.iter().position(|shape| !shape.is_valid()) {
+        eprintln!("{}: shape {} has too few points", input, id + 1);
+        return Err(CliError::Invalid);
+    }
+    if Document::is_svg(Path::new(output)) && shapes.iter().any(has_constraints) {
+        eprintln!("SVG keeps only geometry, constraints are not written");
+    }
+    Document::new(shapes, vec![])
+        .save(Path::new(output))
+        .map_err(|e| CliError::File(format!("{}: {}", output, e)))
+}
+
+fn has_constraints(shape: &Shape) -> bool {
+    std::iter::once(shape.outline())
+        .chain(shape.holes())
+        .flatten()
+        .any(Point::has_constraint)
+}
+
+fn render(input: &str, output: &str, options: &Options) -> Result<(), CliError> {
+    let shapes = load(input)?;
+    let canvas =
+        Canvas::render(&shapes, options.scale, options.tolerance).map_err(CliError::Usage)?;
+    canvas
+        .save_png(Path::new(output))
+        .map_err(|e| CliError::File(format!("{}: {}", output, e)))
+}
+
+fn measure(file: &str, options: &Options) -> Result<(), CliError> {
+    let shapes = load(file)?;
+    let mut report = vec![];
+    for (shape_id, shape) in shapes.iter().enumerate() {
+        let outline = shape.outline();
+        let closed = shape.is_closed();
+        let rings = shape.flattened_rings(options.tolerance);
+        let kind = if closed { "polygon" } else { "open path" };
+        report.push(format!("shape {}: {}", shape_id + 1, kind));
+        if !shape.is_valid() {
+            report.push("  too few points to measure".to_string());
+            continue;
+        }
+        report.push(format!("  vertices: {}", outline.len()));
+        report.push(format!("  holes: {}", shape.holes().len()));
+        report.push(format!(
+            "  constrained edges: {}",
+            outline
+                .iter()
+                .filter(|point| point.has_constraint())
+                .count()
+        ));
+        report.push(format!(
+            "  curves: {}",
+            outline
+                .iter()
+                .filter(|point| point.is_start_of_curve_segment())
+                .count()
+        ));
+        if closed {
+            report.push(format!(
+                "  perimeter: {:.2}",
+                Point::perimeter(outline, true)
+            ));
+            // Holes are always subtracted, whichever direction their vertices go
+            let area = rings
+                .iter()
+                .enumerate()
+                .map(|(id, ring)| match id {
+                    0 => Geometry::signed_area(ring).abs(),
+                    _ => -Geometry::signed_area(ring).abs(),
+                })
+                .sum::<f32>();
+            report.push(format!("  area: {:.2}", area));
+        } else {
+            report.push(format!("  length: {:.2}", Point::perimeter(outline, false)));
+        }
+        if !rings[0].is_empty() {
+            let bounds = Rect::from_points(&rings[0]);
+            report.push(format!(
+                "  bounds: ({:.2}, {:.2}) - ({:.2}, {:.2})",
+                bounds.min.x, bounds.min.y, bounds.max.x, bounds.max.y
+            ));
+        }
+        report.push(format!(
+            "  self-intersections: {}",
+            SelfIntersections::find(outline, closed).len()
+        ));
+    }
+    print_lines(&report);
+    Ok(())
+}
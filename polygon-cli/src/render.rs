@@ -0,0 +1,102 @@
+use std::{fs::File, io::BufWriter, path::Path};
+
+use polygon_model::{raster::Raster, shape::Shape, Pos2, Rect, Vec2};
+
+type Color = [u8; 4];
+
+const BACKGROUND_COLOR: Color = [255, 255, 255, 255];
+const POLYGON_COLOR: Color = [0, 0, 0, 255];
+const OPEN_PATH_COLOR: Color = [0, 0, 200, 255];
+
+/// RGBA image the shapes are rasterized into
+pub struct Canvas {
+    width: u32,
+    height: u32,
+    pixels: Vec<u8>,
+}
+
+impl Canvas {
+    /// Empty space around the shapes, in pixels of the image
+    const MARGIN: f32 = 10.0;
+    /// Largest allowed width and height of the image
+    const MAX_SIZE: f32 = 16384.0;
+
+    fn new(width: u32, height: u32) -> Self {
+        Self {
+            width,
+            height,
+            pixels: BACKGROUND_COLOR.repeat((width * height) as usize),
+        }
+    }
+
+    /// Draws outlines of all shapes (with the Bresenham algorithm, like the editor) on an image
+    /// just big enough to hold them, with coordinates multiplied by `scale`
+    pub fn render(shapes: &[Shape], scale: f32, tolerance: f32) -> Result<Self, String> {
+        let rings: Vec<(Vec<Pos2>, bool)> = shapes
+            .iter()
+            .flat_map(|shape| {
+                shape
+                    .flattened_rings(tolerance / scale)
+                    .into_iter()
+                    .map(|ring| (ring, shape.is_closed()))
+            })
+            .collect();
+        let points: Vec<Pos2> = rings.iter().flat_map(|(ring, _)| ring.clone()).collect();
+        let bounds = if points.is_empty() {
+            Rect::ZERO
+        } else {
+            Rect::from_points(&points)
+        };
+        let size = bounds.size() * scale + Vec2::splat(2.0 * Self::MARGIN);
+        if size.x > Self::MAX_SIZE || size.y > Self::MAX_SIZE {
+            return Err(format!(
+                "Image would be {:.0}x{:.0} pixels, use smaller scale",
+                size.x, size.y
+            ));
+        }
+        let mut canvas = Self::new(size.x.ceil() as u32, size.y.ceil() as u32);
+        let to_image =
+            |pos: Pos2| ((pos - bounds.min) * scale + Vec2::splat(Self::MARGIN)).to_pos2();
+        for (ring, closed) in rings {
+            let ring: Vec<Pos2> = ring.into_iter().map(to_image).collect();
+            let color = if closed {
+                POLYGON_COLOR
+            } else {
+                OPEN_PATH_COLOR
+            };
+            canvas.draw_polyline(&ring, closed, color);
+        }
+        Ok(canvas)
+    }
+
+    fn draw_polyline(&mut self, points: &[Pos2], closed: bool, color: Color) {
+        let edges_count = if closed {
+            points.len()
+        } else {
+            points.len().saturating_sub(1)
+        };
+        for id in 0..edges_count {
+            let next = (id + 1) % points.len();
+            Raster::line(points[id], points[next], |x, y| self.set_pixel(x, y, color));
+        }
+    }
+
+    fn set_pixel(&mut self, x: i32, y: i32, color: Color) {
+        if x < 0 || y < 0 || x as u32 >= self.width || y as u32 >= self.height {
+            return;
+        }
+        let id = 4 * (y as usize * self.width as usize + x as usize);
+        self.pixels[id..id + 4].copy_from_slice(&color);
+    }
+
+    pub fn save_png(&self, path: &Path) -> Result<(), String> {
+        let file = File::create(path).map_err(|e| format!("Can't access file: {}", e))?;
+        let mut encoder = png::Encoder::new(BufWriter::new(file), self.width, self.height);
+        encoder.set_color(png::ColorType::Rgba);
+        encoder.set_depth(png::BitDepth::Eight);
+        encoder
+            .write_header()
+            .and_then(|mut writer| writer.write_image_data(&self.pixels))
+            .map_err(|e| format!("Can't write PNG: {}", e))
+    }
+}
@@ -0,0 +1,116 @@
+use std::{fs, process::Command};
+
+const SQUARE_SVG: &str =
+    r#"<svg xmlns="http://www.w3.org/2000/svg"><path d="M0 0 L100 0 L100 100 L0 100 Z"/></svg>"#;
+const BOW_TIE_SVG: &str =
+    r#"<svg xmlns="http://www.w3.org/2000/svg"><path d="M0 0 L50 50 L50 0 L0 50 Z"/></svg>"#;
+const EMPTY_OUTLINE_JSON: &str = r#"{"shapes":[{"outline":[],"holes":[]}]}"#;
+/// Triangle saved before edges could be other curves than cubic bezier segments
+const OLD_FORMAT_JSON: &str = r#"{"shapes":[{"outline":[
+    {"pos":{"x":0,"y":0},"constraint":null,"bezier_data":{"inner_points":[{"x":30,"y":-20},{"x":70,"y":-20}]},"continuity_type":"C1"},
+    {"pos":{"x":100,"y":0},"constraint":null,"bezier_data":null,"continuity_type":"C1"},
+    {"pos":{"x":50,"y":80},"constraint":null,"bezier_data":null,"continuity_type":"C1"}],"holes":[]}]}"#;
+
+/// Triangle whose first edge is marked horizontal, but isn't
+fn broken_constraint_json() -> String {
+    let point = |x: f32, y: f32, constraint: &str| {
+        format!(
+            r#"{{"pos":{{"x":{},"y":{}}},"constraint":{},"segment":"Line","continuity_type":"C1"}}"#,
+            x, y, constraint
+        )
+    };
+    format!(
+        r#"{{"shapes":[{{"outline":[{},{},{}],"holes":[]}}]}}"#,
+        point(0.0, 0.0, r#""Horizontal""#),
+        point(100.0, 30.0, "null"),
+        point(50.0, 80.0, "null")
+    )
+}
+
+/// Returns path inside a directory of the test, so that tests running in parallel don't clash
+fn output_path(test: &str, name: &str) -> String {
+    let dir = std::env::temp_dir().join(format!("polygon-cli-{}-{}", std::process::id(), test));
+    fs::create_dir_all(&dir).unwrap();
+    dir.join(name).to_string_lossy().into_owned()
+}
+
+fn file(test: &str, name: &str, contents: &str) -> String {
+    let path = output_path(test, name);
+    fs::write(&path, contents).unwrap();
+    path
+}
+
+fn exit_code(args: &[&str]) -> i32 {
+    Command::new(env!("CARGO_BIN_EXE_polygon-cli"))
+        .args(args)
+        .output()
+        .unwrap()
+        .status
+        .code()
+        .unwrap()
+}
+
+#[test]
+fn validate_exit_codes() {
+    let square = file("validate", "square.svg", SQUARE_SVG);
+    let bow_tie = file("validate", "bow_tie.svg", BOW_TIE_SVG);
+    let broken = file("validate", "broken.json", &broken_constraint_json());
+    let old_format = file("validate", "old_format.json", OLD_FORMAT_JSON);
+    // Polygon opened at a vertex has both ends in the same point, which is not an intersection
+    let opened = file(
+        "validate",
+        "opened.svg",
+        r#"<path d="M0 0 L100 0 L100 100 L0 100 L0 0"/>"#,
+    );
+    assert_eq!(exit_code(&["validate", &square]), 0);
+    assert_eq!(exit_code(&["validate", &opened]), 0);
+    assert_eq!(exit_code(&["validate", &old_format]), 0);
+    assert_eq!(exit_code(&["validate", &bow_tie]), 1);
+    assert_eq!(exit_code(&["validate", &broken]), 1);
+    assert_eq!(exit_code(&["validate"]), 2);
+    assert_eq!(exit_code(&["validate", "/nonexistent/polygon.json"]), 3);
+}
+
+#[test]
+fn convert_exit_codes() {
+    let square = file("convert", "square.svg", SQUARE_SVG);
+    let empty = file("convert", "empty.json", EMPTY_OUTLINE_JSON);
+    let invalid_svg = file("convert", "invalid.svg", r#"<path d="L10 10"/>"#);
+    let json = output_path("convert", "square.json");
+    assert_eq!(exit_code(&["convert", &square, &json]), 0);
+    assert!(fs::read_to_string(&json).unwrap().contains("outline"));
+    let svg = output_path("convert", "back.svg");
+    assert_eq!(exit_code(&["convert", &json, &svg]), 0);
+    assert_eq!(
+        exit_code(&["convert", &empty, &output_path("convert", "out.svg")]),
+        1
+    );
+    assert_eq!(exit_code(&["convert", &square]), 2);
+    assert_eq!(exit_code(&["convert", &invalid_svg, &json]), 3);
+    assert_eq!(exit_code(&["convert", &square, "/nonexistent/out.json"]), 3);
+}
+
+#[test]
+fn render_exit_codes() {
+    let square = file("render", "square.svg", SQUARE_SVG);
+    let png = output_path("render", "square.png");
+    assert_eq!(exit_code(&["render", &square, &png, "--scale", "2"]), 0);
+    assert!(fs::read(&png).unwrap().starts_with(b"\x89PNG"));
+    assert_eq!(exit_code(&["render", &square, &png, "--scale", "abc"]), 2);
+    assert_eq!(exit_code(&["render", "/nonexistent/polygon.json", &png]), 3);
+}
+
+#[test]
+fn measure_exit_codes() {
+    let square = file("measure", "square.svg", SQUARE_SVG);
+    // Drawing after closing a subpath starts a new one in its first point
+    let reopened = file(
+        "measure",
+        "reopened.svg",
+        r#"<path d="M0 0 L100 0 L50 80 Z L10 10"/>"#,
+    );
+    assert_eq!(exit_code(&["measure", &square]), 0);
+    assert_eq!(exit_code(&["measure", &reopened]), 0);
+    assert_eq!(exit_code(&["measure"]), 2);
+    assert_eq!(exit_code(&["measure", "/nonexistent/polygon.json"]), 3);
+}
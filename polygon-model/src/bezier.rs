@@ -0,0 +1,327 @@
+use emath::Pos2;
+use serde::{Deserialize, Serialize};
+
+use crate::{geometry::Geometry, point::Point};
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct BezierData {
+    inner_points: [Pos2; 2],
+}
+
+impl BezierData {
+    /// Tolerance below which flattening would only produce more points without visible difference
+    const MIN_FLATTENING_TOLERANCE: f32 = 0.01;
+    /// Limit of subdivisions of a single curve, which bounds number of points to 2^depth
+    const MAX_FLATTENING_DEPTH: usize = 12;
+    /// Nodes and weights of 5-point Gauss-Legendre quadrature on [-1, 1]
+    const GAUSS_LEGENDRE: [(f32, f32); 5] = [
+        (0.0, 0.568_888_9),
+        (-0.538_469_3, 0.478_628_67),
+        (0.538_469_3, 0.478_628_67),
+        (-0.906_179_85, 0.236_926_88),
+        (0.906_179_85, 0.236_926_88),
+    ];
+    /// Parameter range is split into this many parts, each of them integrated separately
+    const ARC_LENGTH_PARTS: usize = 8;
+    /// Largest accepted difference between requested and found length when searching for parameter
+    const ARC_LENGTH_TOLERANCE: f32 = 1e-3;
+    const MAX_ARC_LENGTH_ITERATIONS: usize = 32;
+
+    pub fn new(inner_points: [Pos2; 2]) -> Self {
+        Self { inner_points }
+    }
+
+    pub fn inner_points(&self) -> &[Pos2; 2] {
+        &self.inner_points
+    }
+
+    pub fn inner_points_mut(&mut self) -> &mut [Pos2; 2] {
+        &mut self.inner_points
+    }
+
+    pub fn update_inner_point_position(&mut self, index: usize, new_position: Pos2) {
+        self.inner_points[index] = new_position;
+    }
+
+    /// Returns points on cubic curve given by all its control points, such that polyline connecting them
+    /// (together with the last control point) is never further than `tolerance` from the curve.
+    /// First and last control points are not included.
+    ///
+    /// Curve is recursively split in half (de Casteljau algorithm) until inner control points of each part
+    /// are close enough to its chord. Curve lies inside convex hull of its control points, so the chord
+    /// is then never further than `tolerance` from that part of the curve.
+    pub fn flatten_control_polygon(control_points: &[Pos2; 4], tolerance: f32) -> Vec<Pos2> {
+        let mut points = vec![];
+        Self::flatten_recursive(
+            control_points,
+            tolerance.max(Self::MIN_FLATTENING_TOLERANCE),
+            0,
+            &mut points,
+        );
+        // Last point is the last control point
+        points.pop();
+        points
+    }
+
+    fn flatten_recursive(
+        control_points: &[Pos2; 4],
+        tolerance: f32,
+        depth: usize,
+        points: &mut Vec<Pos2>,
+    ) {
+        let [p0, p1, p2, p3] = *control_points;
+        let is_flat = Geometry::distance_to_segment(p1, p0, p3)
+            .max(Geometry::distance_to_segment(p2, p0, p3))
+            <= tolerance;
+        if is_flat || depth >= Self::MAX_FLATTENING_DEPTH {
+            points.push(p3);
+            return;
+        }
+        let (first, second) = Self::split_control_polygon(control_points, 0.5);
+        Self::flatten_recursive(&first, tolerance, depth + 1, points);
+        Self::flatten_recursive(&second, tolerance, depth + 1, points);
+    }
+
+    /// Returns all four control points of the segment, starting with `start` and ending with `end`
+    pub fn control_polygon(&self, start: &Point, end: &Point) -> [Pos2; 4] {
+        [
+            *start.pos(),
+            self.inner_points[0],
+            self.inner_points[1],
+            *end.pos(),
+        ]
+    }
+
+    /// Splits cubic curve given by its control points at parameter t (de Casteljau algorithm)
+    pub fn split_control_polygon(control_points: &[Pos2; 4], t: f32) -> ([Pos2; 4], [Pos2; 4]) {
+        let [p0, p1, p2, p3] = *control_points;
+        let p01 = p0.lerp(p1, t);
+        let p12 = p1.lerp(p2, t);
+        let p23 = p2.lerp(p3, t);
+        let p012 = p01.lerp(p12, t);
+        let p123 = p12.lerp(p23, t);
+        let middle = p012.lerp(p123, t);
+        ([p0, p01, p012, middle], [middle, p123, p23, p3])
+    }
+
+    /// Returns length of cubic curve given by its control points, measured from its start to parameter t.
+    /// Speed |B'(t)| is integrated with Gauss-Legendre quadrature.
+    pub fn arc_length(control_points: &[Pos2; 4], t: f32) -> f32 {
+        let polynomial_base = Self::polynomial_base(control_points);
+        let part_length = t / Self::ARC_LENGTH_PARTS as f32;
+        (0..Self::ARC_LENGTH_PARTS)
+            .map(|part| {
+                let middle = (part as f32 + 0.5) * part_length;
+                Self::GAUSS_LEGENDRE
+                    .iter()
+                    .map(|(node, weight)| {
+                        let t = middle + node * part_length / 2.0;
+                        weight
+                            * Self::first_derivative_in_polynomial_base(&polynomial_base, t)
+                                .length()
+                    })
+                    .sum::<f32>()
+                    * part_length
+                    / 2.0
+            })
+            .sum()
+    }
+
+    /// Returns parameter t, for which length of the curve from its start to t equals `length`.
+    /// Newton's method is used, with bisection whenever its step leaves the range known to contain the result.
+    pub fn parameter_at_length(control_points: &[Pos2; 4], length: f32) -> f32 {
+        let total_length = Self::arc_length(control_points, 1.0);
+        if length <= 0.0 || total_length <= f32::EPSILON {
+            return 0.0;
+        }
+        if length >= total_length {
+            return 1.0;
+        }
+        let polynomial_base = Self::polynomial_base(control_points);
+        let (mut low, mut high) = (0.0, 1.0);
+        let mut t = length / total_length;
+        for _ in 0..Self::MAX_ARC_LENGTH_ITERATIONS {
+            let error = Self::arc_length(control_points, t) - length;
+            if error.abs() <= Self::ARC_LENGTH_TOLERANCE {
+                break;
+            }
+            if error > 0.0 {
+                high = t;
+            } else {
+                low = t;
+            }
+            let speed = Self::first_derivative_in_polynomial_base(&polynomial_base, t).length();
+            let newton_step = t - error / speed;
+            t = if speed > f32::EPSILON && newton_step > low && newton_step < high {
+                newton_step
+            } else {
+                (low + high) / 2.0
+            };
+        }
+        t
+    }
+
+    /// Returns `count` points lying on the curve at equal distances (measured along it), including its ends.
+    /// Single point is the start of the curve.
+    pub fn evenly_spaced_points(control_points: &[Pos2; 4], count: usize) -> Vec<Pos2> {
+        let total_length = Self::arc_length(control_points, 1.0);
+        let polynomial_base = Self::polynomial_base(control_points);
+        let intervals_count = count.saturating_sub(1).max(1);
+        (0..count)
+            .map(|i| {
+                let length = total_length * i as f32 / intervals_count as f32;
+                let t = Self::parameter_at_length(control_points, length);
+                Self::evaluate_polynomial_base(&polynomial_base, t)
+            })
+            .collect()
+    }
+
+    /// Returns point of cubic curve given by its control points at parameter t
+    pub fn point_at(control_points: &[Pos2; 4], t: f32) -> Pos2 {
+        Self::evaluate_polynomial_base(&Self::polynomial_base(control_points), t)
+    }
+
+    /// Returns first and second derivative of cubic curve given by its control points at parameter t
+    pub fn derivatives_at(control_points: &[Pos2; 4], t: f32) -> (emath::Vec2, emath::Vec2) {
+        let polynomial_base = Self::polynomial_base(control_points);
+        (
+            Self::first_derivative_in_polynomial_base(&polynomial_base, t),
+            Self::second_derivative_in_polynomial_base(&polynomial_base, t),
+        )
+    }
+
+    /// Returns signed curvature of cubic curve given by its control points at parameter t.
+    /// Curvature is positive where the curve turns counterclockwise (in coordinates where y grows upwards).
+    pub fn curvature_at(control_points: &[Pos2; 4], t: f32) -> f32 {
+        Self::curvature_in_polynomial_base(&Self::polynomial_base(control_points), t)
+    }
+
+    /// Returns teeth of curvature comb of cubic curve given by its control points, each as a pair of
+    /// point on the curve and end of the tooth. Teeth are normals scaled by curvature times `scale`,
+    /// placed at `teeth_count` regular parameter steps, and stick out on the convex side of the curve.
+    pub fn curvature_comb(
+        control_points: &[Pos2; 4],
+        teeth_count: usize,
+        scale: f32,
+    ) -> Vec<[Pos2; 2]> {
+        let polynomial_base = Self::polynomial_base(control_points);
+        (0..=teeth_count)
+            .map(|i| {
+                let t = i as f32 / teeth_count.max(1) as f32;
+                let point = Self::evaluate_polynomial_base(&polynomial_base, t);
+                let tangent =
+                    Self::first_derivative_in_polynomial_base(&polynomial_base, t).normalized();
+                let normal = emath::Vec2::new(-tangent.y, tangent.x);
+                let curvature = Self::curvature_in_polynomial_base(&polynomial_base, t);
+                [point, point - normal * curvature * scale]
+            })
+            .collect()
+    }
+
+    fn first_derivative_in_polynomial_base(polynomial_base: &[Pos2; 4], t: f32) -> emath::Vec2 {
+        polynomial_base[1].to_vec2()
+            + t * (2.0 * polynomial_base[2].to_vec2() + 3.0 * t * polynomial_base[3].to_vec2())
+    }
+
+    fn second_derivative_in_polynomial_base(polynomial_base: &[Pos2; 4], t: f32) -> emath::Vec2 {
+        2.0 * polynomial_base[2].to_vec2() + 6.0 * t * polynomial_base[3].to_vec2()
+    }
+
+    fn curvature_in_polynomial_base(polynomial_base: &[Pos2; 4], t: f32) -> f32 {
+        let first = Self::first_derivative_in_polynomial_base(polynomial_base, t);
+        let second = Self::second_derivative_in_polynomial_base(polynomial_base, t);
+        let speed = first.length();
+        if speed <= f32::EPSILON {
+            return 0.0;
+        }
+        (first.x * second.y - first.y * second.x) / (speed * speed * speed)
+    }
+
+    fn evaluate_polynomial_base(polynomial_base: &[Pos2; 4], t: f32) -> Pos2 {
+        (polynomial_base[0].to_vec2()
+            + t * (polynomial_base[1].to_vec2()
+                + t * (polynomial_base[2].to_vec2() + t * polynomial_base[3].to_vec2())))
+        .to_pos2()
+    }
+
+    /// Returns coordinates in polynomial base, where at i-th index is i-th coordinate
+    fn polynomial_base(control_points: &[Pos2; 4]) -> [Pos2; 4] {
+        let [v0, v1, v2, v3] = *control_points;
+
+        let a0 = v0;
+        let a1 = (3.0 * (v1 - v0)).to_pos2();
+        let a2 = (3.0 * (v2 - 2.0 * v1 + v0.to_vec2())).to_pos2();
+        let a3 = (v3 - 3.0 * v2 + 3.0 * v1.to_vec2() - v0.to_vec2()).to_pos2();
+
+        [a0, a1, a2, a3]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use emath::pos2;
+
+    use super::*;
+
+    /// Straight curve with control points at thirds, so that its parameter grows with length
+    const STRAIGHT: [Pos2; 4] = [
+        pos2(0.0, 0.0),
+        pos2(100.0 / 3.0, 0.0),
+        pos2(200.0 / 3.0, 0.0),
+        pos2(100.0, 0.0),
+    ];
+    /// Curve whose control points are bunched towards its start
+    const UNEVEN: [Pos2; 4] = [
+        pos2(0.0, 0.0),
+        pos2(5.0, 40.0),
+        pos2(10.0, 40.0),
+        pos2(100.0, 0.0),
+    ];
+
+    #[test]
+    fn arc_length_of_straight_curve_is_its_chord() {
+        assert!((BezierData::arc_length(&STRAIGHT, 1.0) - 100.0).abs() < 0.01);
+        assert!((BezierData::arc_length(&STRAIGHT, 0.5) - 50.0).abs() < 0.01);
+    }
+
+    #[test]
+    fn parameter_at_length_inverts_arc_length() {
+        assert!((BezierData::parameter_at_length(&STRAIGHT, 25.0) - 0.25).abs() < 1e-3);
+        let total_length = BezierData::arc_length(&UNEVEN, 1.0);
+        for fraction in [0.0, 0.2, 0.5, 0.9, 1.0] {
+            let t = BezierData::parameter_at_length(&UNEVEN, total_length * fraction);
+            let length = BezierData::arc_length(&UNEVEN, t);
+            assert!((length - total_length * fraction).abs() < 0.05);
+        }
+    }
+
+    #[test]
+    fn evenly_spaced_points_split_the_curve_into_equal_parts() {
+        assert!(BezierData::evenly_spaced_points(&UNEVEN, 0).is_empty());
+        assert_eq!(
+            BezierData::evenly_spaced_points(&UNEVEN, 1),
+            vec![UNEVEN[0]]
+        );
+        let points = BezierData::evenly_spaced_points(&UNEVEN, 5);
+        assert_eq!(points.len(), 5);
+        assert!(points[4].distance(UNEVEN[3]) < 0.01);
+        let lengths: Vec<f32> = points
+            .iter()
+            .map(|point| {
+                // Parameter of the point is found by sampling the curve densely
+                let t = (0..=1000)
+                    .map(|i| i as f32 / 1000.0)
+                    .min_by(|a, b| {
+                        let distance = |t| BezierData::point_at(&UNEVEN, t).distance(*point);
+                        distance(*a).total_cmp(&distance(*b))
+                    })
+                    .unwrap();
+                BezierData::arc_length(&UNEVEN, t)
+            })
+            .collect();
+        let step = lengths[4] / 4.0;
+        for (i, length) in lengths.iter().enumerate() {
+            assert!((length - step * i as f32).abs() < 0.5);
+        }
+    }
+}
@@ -0,0 +1,347 @@
+use emath::{Pos2, Rect, Vec2};
+
+use crate::{geometry::Geometry, point::Point, shape::Shape};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BooleanOperation {
+    Union,
+    Intersection,
+    Difference,
+    Xor,
+}
+
+impl BooleanOperation {
+    pub const ALL: [BooleanOperation; 4] = [
+        BooleanOperation::Union,
+        BooleanOperation::Intersection,
+        BooleanOperation::Difference,
+        BooleanOperation::Xor,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            BooleanOperation::Union => "Union",
+            BooleanOperation::Intersection => "Intersection",
+            BooleanOperation::Difference => "Difference",
+            BooleanOperation::Xor => "XOR",
+        }
+    }
+
+    /// Whether point that is (or is not) inside both operands is part of the result
+    fn includes(&self, inside_first: bool, inside_second: bool) -> bool {
+        match self {
+            BooleanOperation::Union => inside_first || inside_second,
+            BooleanOperation::Intersection => inside_first && inside_second,
+            BooleanOperation::Difference => inside_first && !inside_second,
+            BooleanOperation::Xor => inside_first != inside_second,
+        }
+    }
+}
+
+/// Directed edge between two vertices from the vertex pool
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Edge {
+    from: usize,
+    to: usize,
+}
+
+/// Set of vertices in which positions closer than the tolerance are merged into one vertex,
+/// so that edges meeting in (almost) the same place can be linked together
+#[derive(Debug, Default)]
+struct VertexPool {
+    positions: Vec<Pos2>,
+}
+
+impl VertexPool {
+    const MERGE_TOLERANCE: f32 = 1e-2;
+
+    fn insert(&mut self, pos: Pos2) -> usize {
+        match self
+            .positions
+            .iter()
+            .position(|p| p.distance(pos) < Self::MERGE_TOLERANCE)
+        {
+            Some(id) => id,
+            None => {
+                self.positions.push(pos);
+                self.positions.len() - 1
+            }
+        }
+    }
+
+    fn pos(&self, id: usize) -> Pos2 {
+        self.positions[id]
+    }
+}
+
+/// Boolean operations on shapes.
+///
+/// Both operands are flattened and all their edges are split in every point where they cross or touch
+/// any other edge. Each resulting piece lies entirely inside or outside of each operand, so it is kept only
+/// when the result of the operation differs on its both sides (checked with even-odd rule, which makes
+/// self-intersecting and self-touching inputs work too). Kept pieces are oriented to have the result on
+/// their left side and linked back into rings.
+pub struct Clipper;
+
+impl Clipper {
+    /// Returns shapes covering the result of the operation, curves of the operands are flattened
+    /// with given tolerance
+    pub fn apply(
+        first: &Shape,
+        second: &Shape,
+        operation: BooleanOperation,
+        tolerance: f32,
+    ) -> Vec<Shape> {
+        let first_rings = first.flattened_rings(tolerance);
+        let second_rings = second.flattened_rings(tolerance);
+        let all_rings: Vec<Vec<Pos2>> = first_rings
+            .iter()
+            .chain(second_rings.iter())
+            .cloned()
+            .collect();
+
+        Self::extract_region(&all_rings, |pos| {
+            operation.includes(
+                Geometry::contains_point(&first_rings, pos),
+                Geometry::contains_point(&second_rings, pos),
+            )
+        })
+    }
+
+    /// Returns shapes covering the region where `is_inside` holds, whose boundary has to be made of edges of `rings`.
+    /// Outlines go counterclockwise and holes clockwise (in coordinates where y grows upwards), so that
+    /// the region is always on their left side. Points of the result have no constraints or curves.
+    pub(crate) fn extract_region(
+        rings: &[Vec<Pos2>],
+        is_inside: impl Fn(Pos2) -> bool,
+    ) -> Vec<Shape> {
+        let mut pool = VertexPool::default();
+        let mut edges: Vec<Edge> = vec![];
+        for (start, end) in Self::split_edges(rings) {
+            let direction = end - start;
+            let length = direction.length();
+            if length <= f32::EPSILON {
+                continue;
+            }
+            let middle = start.lerp(end, 0.5);
+            let offset = Vec2::new(-direction.y, direction.x) / length * (0.25 * length).min(0.05);
+            let included_left = is_inside(middle + offset);
+            let included_right = is_inside(middle - offset);
+            if included_left == included_right {
+                continue;
+            }
+            let edge = if included_left {
+                Edge {
+                    from: pool.insert(start),
+                    to: pool.insert(end),
+                }
+            } else {
+                Edge {
+                    from: pool.insert(end),
+                    to: pool.insert(start),
+                }
+            };
+            // Pieces shared by more rings are found more than once, but only one of them is needed
+            if edge.from == edge.to || edges.contains(&edge) {
+                continue;
+            }
+            edges.push(edge);
+        }
+
+        let rings = Self::link_edges(&pool, &edges);
+        Self::assemble_shapes(rings)
+    }
+
+    /// Splits edges of `rings` in all points where they meet any other edge
+    fn split_edges(rings: &[Vec<Pos2>]) -> Vec<(Pos2, Pos2)> {
+        let all_edges: Vec<(Pos2, Pos2)> = rings
+            .iter()
+            .flat_map(|ring| Self::ring_edges(ring))
+            .collect();
+
+        let mut pieces = vec![];
+        for (start, end) in all_edges.iter().copied() {
+            let edge_box = Rect::from_two_pos(start, end).expand(Geometry::SAME_POINT_TOLERANCE);
+            let mut split_parameters = vec![0.0, 1.0];
+            for (other_start, other_end) in all_edges.iter().copied() {
+                if !edge_box.intersects(Rect::from_two_pos(other_start, other_end)) {
+                    continue;
+                }
+                // Vertices of other edges lying on this edge split it too (touching without crossing)
+                for other in [other_start, other_end] {
+                    if Geometry::distance_to_segment(other, start, end)
+                        < VertexPool::MERGE_TOLERANCE
+                    {
+                        let direction = end - start;
+                        split_parameters.push(
+                            ((other - start).dot(direction) / direction.length_sq())
+                                .clamp(0.0, 1.0),
+                        );
+                    }
+                }
+                for (t, _) in Geometry::intersect_segments(start, end, other_start, other_end) {
+                    split_parameters.push(t);
+                }
+            }
+            split_parameters.sort_by(f32::total_cmp);
+            split_parameters.dedup_by(|a, b| (*a - *b).abs() < f32::EPSILON);
+            for window in split_parameters.windows(2) {
+                pieces.push((start.lerp(end, window[0]), start.lerp(end, window[1])));
+            }
+        }
+        pieces
+    }
+
+    fn ring_edges(ring: &[Pos2]) -> impl Iterator<Item = (Pos2, Pos2)> + '_ {
+        (0..ring.len()).map(|id| (ring[id], ring[(id + 1) % ring.len()]))
+    }
+
+    /// Links directed edges into closed rings. When more edges leave the same vertex, the one that turns
+    /// the most to the left is chosen, so that rings touching in a single vertex are kept separate.
+    fn link_edges(pool: &VertexPool, edges: &[Edge]) -> Vec<Vec<Pos2>> {
+        let mut used = vec![false; edges.len()];
+        let mut rings = vec![];
+        for first_edge in 0..edges.len() {
+            if used[first_edge] {
+                continue;
+            }
+            used[first_edge] = true;
+            let mut ring = vec![pool.pos(edges[first_edge].from)];
+            let mut current = first_edge;
+            loop {
+                let vertex = edges[current].to;
+                if vertex == edges[first_edge].from {
+                    break;
+                }
+                ring.push(pool.pos(vertex));
+                let incoming = pool.pos(vertex) - pool.pos(edges[current].from);
+                let next = (0..edges.len())
+                    .filter(|id| !used[*id] && edges[*id].from == vertex)
+                    .min_by(|a, b| {
+                        let angle_a = Self::clockwise_angle(
+                            -incoming,
+                            pool.pos(edges[*a].to) - pool.pos(vertex),
+                        );
+                        let angle_b = Self::clockwise_angle(
+                            -incoming,
+                            pool.pos(edges[*b].to) - pool.pos(vertex),
+                        );
+                        angle_a.total_cmp(&angle_b)
+                    });
+                match next {
+                    Some(next) => {
+                        used[next] = true;
+                        current = next;
+                    }
+                    // Open chain can only come from numerical problems, it is dropped
+                    None => {
+                        ring.clear();
+                        break;
+                    }
+                }
+            }
+            Geometry::remove_collinear_points(&mut ring, VertexPool::MERGE_TOLERANCE);
+            if ring.len() >= 3 {
+                rings.push(ring);
+            }
+        }
+        rings
+    }
+
+    /// Angle in range (0, 2pi] by which `from` has to be rotated clockwise to point in the same direction as `to`
+    fn clockwise_angle(from: Vec2, to: Vec2) -> f32 {
+        let angle = (-Geometry::cross(from, to)).atan2(from.dot(to));
+        if angle <= 0.0 {
+            angle + std::f32::consts::TAU
+        } else {
+            angle
+        }
+    }
+
+    /// Groups rings into shapes: rings with area on their left side are outlines,
+    /// the rest are holes and belong to the smallest outline containing them
+    fn assemble_shapes(rings: Vec<Vec<Pos2>>) -> Vec<Shape> {
+        let (outlines, holes): (Vec<_>, Vec<_>) = rings
+            .into_iter()
+            .partition(|ring| Geometry::signed_area(ring) > 0.0);
+
+        let mut shapes_holes: Vec<Vec<Vec<Pos2>>> = vec![vec![]; outlines.len()];
+        for hole in holes {
+            // Point slightly to the right of the first edge lies inside the hole
+            let direction = hole[1] - hole[0];
+            let inside = hole[0].lerp(hole[1], 0.5)
+                + Vec2::new(direction.y, -direction.x).normalized() * VertexPool::MERGE_TOLERANCE;
+            let owner = (0..outlines.len())
+                .filter(|id| Geometry::contains_point(std::slice::from_ref(&outlines[*id]), inside))
+                .min_by(|a, b| {
+                    Geometry::signed_area(&outlines[*a])
+                        .total_cmp(&Geometry::signed_area(&outlines[*b]))
+                });
+            if let Some(owner) = owner {
+                shapes_holes[owner].push(hole);
+            }
+        }
+
+        outlines
+            .into_iter()
+            .zip(shapes_holes)
+            .map(|(outline, holes)| {
+                Shape::new(
+                    Self::ring_to_points(outline),
+                    holes.into_iter().map(Self::ring_to_points).collect(),
+                )
+            })
+            .collect()
+    }
+
+    fn ring_to_points(ring: Vec<Pos2>) -> Vec<Point> {
+        ring.into_iter().map(Point::new).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::point::fixtures::area;
+
+    fn square(x: f32, y: f32, size: f32) -> Shape {
+        Shape::new(crate::point::fixtures::square(x, y, size), vec![])
+    }
+
+    #[test]
+    fn operations_on_overlapping_squares() {
+        let first = square(0.0, 0.0, 100.0);
+        let second = square(50.0, 0.0, 100.0);
+        let expected_areas = [
+            (BooleanOperation::Union, 15000.0),
+            (BooleanOperation::Intersection, 5000.0),
+            (BooleanOperation::Difference, 5000.0),
+            (BooleanOperation::Xor, 10000.0),
+        ];
+        for (operation, expected_area) in expected_areas {
+            let result = Clipper::apply(&first, &second, operation, 0.5);
+            assert!(
+                (area(&result) - expected_area).abs() < 1.0,
+                "{}",
+                operation.name()
+            );
+        }
+    }
+
+    #[test]
+    fn difference_of_inner_square_makes_hole() {
+        let outer = square(0.0, 0.0, 100.0);
+        let inner = square(25.0, 25.0, 50.0);
+        let result = Clipper::apply(&outer, &inner, BooleanOperation::Difference, 0.5);
+        assert_eq!(result.len(), 1);
+        assert_eq!(result[0].holes().len(), 1);
+        assert!((area(&result) - 7500.0).abs() < 1.0);
+    }
+
+    #[test]
+    fn disjoint_intersection_is_empty() {
+        let first = square(0.0, 0.0, 10.0);
+        let second = square(50.0, 50.0, 10.0);
+        assert!(Clipper::apply(&first, &second, BooleanOperation::Intersection, 0.5).is_empty());
+    }
+}
@@ -0,0 +1,173 @@
+use emath::{Pos2, Vec2};
+
+use crate::{
+    point::{EdgeConstraint, Point},
+    segment::EdgeSegment,
+};
+
+/// Shape that replaces the polygon vertex
+#[derive(Clone, Copy, PartialEq)]
+enum CornerKind {
+    /// Circular arc tangent to both edges
+    Fillet,
+    /// Straight edge cutting the same length from both edges
+    Chamfer,
+}
+
+/// Vertex between two straight edges
+struct Corner {
+    vertex: Pos2,
+    /// Unit vector from the vertex to the previous point
+    to_previous: Vec2,
+    /// Unit vector from the vertex to the next point
+    to_next: Vec2,
+    previous_length: f32,
+    next_length: f32,
+    /// Angle between the edges
+    angle: f32,
+}
+
+/// Rounding and cutting off polygon corners. Only vertices between two straight edges can be changed.
+pub struct Corners;
+
+impl Corners {
+    /// Angles between edges closer than this to 0 or 180 degrees are not treated as corners
+    const MIN_ANGLE: f32 = 1e-3;
+    /// Points closer than this are merged after rounding all corners
+    const MERGE_TOLERANCE: f32 = 1e-2;
+
+    /// Replaces the vertex with circular arc of given radius, approximated with a bezier segment.
+    /// Returns whether the corner was rounded (it's not if the edges are too short for given radius).
+    pub fn fillet(points: &mut Vec<Point>, id: usize, radius: f32) -> bool {
+        let Some(corner) = Self::corner(points, id) else {
+            return false;
+        };
+        let distance = radius / (corner.angle / 2.0).tan();
+        if distance >= corner.previous_length.min(corner.next_length) {
+            return false;
+        }
+        Self::replace_corner(points, id, &corner, distance, CornerKind::Fillet);
+        true
+    }
+
+    /// Replaces the vertex with straight edge of given length.
+    /// Returns whether the corner was cut off (it's not if the edges are too short for given length).
+    pub fn chamfer(points: &mut Vec<Point>, id: usize, length: f32) -> bool {
+        let Some(corner) = Self::corner(points, id) else {
+            return false;
+        };
+        let distance = length / (2.0 * (corner.angle / 2.0).sin());
+        if distance >= corner.previous_length.min(corner.next_length) {
+            return false;
+        }
+        Self::replace_corner(points, id, &corner, distance, CornerKind::Chamfer);
+        true
+    }
+
+    /// Rounds all corners that can be rounded. Each edge is shared by two corners, so radius
+    /// is reduced where needed to fit both arcs on half of the edge. Returns number of rounded corners.
+    pub fn fillet_all(points: &mut Vec<Point>, radius: f32) -> usize {
+        let fillets: Vec<(usize, Corner, f32)> = (0..points.len())
+            .filter_map(|id| {
+                let corner = Self::corner(points, id)?;
+                let distance = (radius / (corner.angle / 2.0).tan())
+                    .min(corner.previous_length / 2.0)
+                    .min(corner.next_length / 2.0);
+                Some((id, corner, distance))
+            })
+            .collect();
+        // Going from the end keeps indices of remaining corners valid
+        for (id, corner, distance) in fillets.iter().rev() {
+            Self::replace_corner(points, *id, corner, *distance, CornerKind::Fillet);
+        }
+        // Arcs that take whole edge meet each other, the empty edge between them is removed
+        let mut id = 0;
+        while id < points.len() && points.len() > 3 {
+            let next = Point::get_next_index(points, id);
+            if !points[id].is_start_of_curve_segment()
+                && points[id].pos().distance(*points[next].pos()) <= Self::MERGE_TOLERANCE
+            {
+                points.remove(id);
+            } else {
+                id += 1;
+            }
+        }
+        fillets.len()
+    }
+
+    /// Checks whether both edges of the vertex are straight lines
+    pub fn can_change_corner(points: &[Point], id: usize) -> bool {
+        let previous = Point::get_previous_index(points, id);
+        points.len() >= 3
+            && !points[previous].is_start_of_curve_segment()
+            && !points[id].is_start_of_curve_segment()
+    }
+
+    fn corner(points: &[Point], id: usize) -> Option<Corner> {
+        if !Self::can_change_corner(points, id) {
+            return None;
+        }
+        let vertex = *points[id].pos();
+        let to_previous = *points[Point::get_previous_index(points, id)].pos() - vertex;
+        let to_next = *points[Point::get_next_index(points, id)].pos() - vertex;
+        let (previous_length, next_length) = (to_previous.length(), to_next.length());
+        if previous_length <= f32::EPSILON || next_length <= f32::EPSILON {
+            return None;
+        }
+        let to_previous = to_previous / previous_length;
+        let to_next = to_next / next_length;
+        let angle = to_previous.dot(to_next).clamp(-1.0, 1.0).acos();
+        if !(Self::MIN_ANGLE..=std::f32::consts::PI - Self::MIN_ANGLE).contains(&angle) {
+            return None;
+        }
+        Some(Corner {
+            vertex,
+            to_previous,
+            to_next,
+            previous_length,
+            next_length,
+            angle,
+        })
+    }
+
+    /// Replaces the vertex with two points lying on its edges in given distance from it
+    fn replace_corner(
+        points: &mut Vec<Point>,
+        id: usize,
+        corner: &Corner,
+        distance: f32,
+        kind: CornerKind,
+    ) {
+        let start = corner.vertex + corner.to_previous * distance;
+        let end = corner.vertex + corner.to_next * distance;
+
+        // Edges got shorter, so constant width can't be kept
+        let previous = Point::get_previous_index(points, id);
+        Self::remove_width_constraint(&mut points[previous]);
+        let mut end_point = points[id];
+        Self::remove_width_constraint(&mut end_point);
+        *end_point.pos_mut() = end;
+        let mut start_point =
+            Point::new_all(start, None, EdgeSegment::Line, *end_point.continuity_type());
+
+        if kind == CornerKind::Fillet {
+            let radius = distance * (corner.angle / 2.0).tan();
+            // Length of control arms for cubic approximation of arc with given sweep angle
+            let sweep = std::f32::consts::PI - corner.angle;
+            let arm = 4.0 / 3.0 * (sweep / 4.0).tan() * radius;
+            start_point
+                .init_bezier_data([start - corner.to_previous * arm, end - corner.to_next * arm]);
+            start_point.apply_G1();
+            end_point.apply_G1();
+        }
+
+        points[id] = end_point;
+        points.insert(id, start_point);
+    }
+
+    fn remove_width_constraint(point: &mut Point) {
+        if let Some(EdgeConstraint::ConstWidth(_)) = point.constraint() {
+            point.remove_constraint();
+        }
+    }
+}
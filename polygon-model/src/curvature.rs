@@ -0,0 +1,74 @@
+use emath::Pos2;
+
+use crate::{bezier::BezierData, point::Point};
+
+/// Curvature comb of the polygon curves and curvature jumps in their joints,
+/// which show how smooth the outline is
+pub struct CurvatureComb;
+
+impl CurvatureComb {
+    /// Number of teeth drawn along each cubic piece of a curve
+    const TEETH_PER_CUBIC: usize = 24;
+
+    /// Returns teeth of the comb (point on the curve, end of the tooth) for every curve of the polygon.
+    /// Teeth of each curve are kept separately, so that their envelopes are not joined.
+    pub fn of_polygon(points: &[Point], scale: f32) -> Vec<Vec<[Pos2; 2]>> {
+        (0..points.len())
+            .filter(|id| points[*id].is_start_of_curve_segment())
+            .map(|id| {
+                let id_next = Point::get_next_index(points, id);
+                let mut teeth = vec![];
+                for control_points in points[id]
+                    .segment()
+                    .to_cubics(&points[id], &points[id_next])
+                {
+                    let cubic_teeth =
+                        BezierData::curvature_comb(&control_points, Self::TEETH_PER_CUBIC, scale);
+                    // First tooth is the same as the last one of the previous piece
+                    let skip = usize::from(!teeth.is_empty());
+                    teeth.extend(cubic_teeth.into_iter().skip(skip));
+                }
+                teeth
+            })
+            .collect()
+    }
+
+    /// Returns difference of curvatures on both sides of each vertex adjacent to a curve,
+    /// together with position of that vertex. Straight edges have zero curvature.
+    pub fn joint_jumps(points: &[Point], closed: bool) -> Vec<(Pos2, f32)> {
+        (0..points.len())
+            .filter(|id| {
+                Point::is_part_of_curve_segment(points, closed, *id)
+                    && !Point::is_endpoint(points, closed, *id)
+            })
+            .map(|id| {
+                let previous_id = Point::get_previous_index(points, id);
+                let curvature_before = Self::edge_curvature(points, previous_id, true);
+                let curvature_after = Self::edge_curvature(points, id, false);
+                (
+                    *points[id].pos(),
+                    (curvature_after - curvature_before).abs(),
+                )
+            })
+            .collect()
+    }
+
+    /// Returns curvature of the edge at its start or at its end
+    fn edge_curvature(points: &[Point], edge_start_index: usize, at_end: bool) -> f32 {
+        if !points[edge_start_index].is_start_of_curve_segment() {
+            return 0.0;
+        }
+        let edge_end_index = Point::get_next_index(points, edge_start_index);
+        let cubics = points[edge_start_index]
+            .segment()
+            .to_cubics(&points[edge_start_index], &points[edge_end_index]);
+        let control_points = if at_end {
+            cubics.last()
+        } else {
+            cubics.first()
+        };
+        control_points.map_or(0.0, |control_points| {
+            BezierData::curvature_at(control_points, if at_end { 1.0 } else { 0.0 })
+        })
+    }
+}
@@ -0,0 +1,151 @@
+use std::{fmt, path::Path};
+
+use emath::Pos2;
+use serde::{Deserialize, Serialize};
+
+use crate::{shape::Shape, svg::Svg};
+
+#[derive(Debug)]
+pub enum DocumentError {
+    Io(std::io::Error),
+    Format(serde_json::Error),
+    Svg(String),
+}
+
+impl fmt::Display for DocumentError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DocumentError::Io(e) => write!(f, "Can't access file: {}", e),
+            DocumentError::Format(e) => write!(f, "Invalid document: {}", e),
+            DocumentError::Svg(e) => write!(f, "Invalid SVG: {}", e),
+        }
+    }
+}
+
+impl std::error::Error for DocumentError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            DocumentError::Io(e) => Some(e),
+            DocumentError::Format(e) => Some(e),
+            DocumentError::Svg(_) => None,
+        }
+    }
+}
+
+impl From<std::io::Error> for DocumentError {
+    fn from(e: std::io::Error) -> Self {
+        DocumentError::Io(e)
+    }
+}
+
+impl From<serde_json::Error> for DocumentError {
+    fn from(e: serde_json::Error) -> Self {
+        DocumentError::Format(e)
+    }
+}
+
+/// Everything that is saved to (or loaded from) a JSON file
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Document {
+    /// All shapes, the first one is the edited one
+    shapes: Vec<Shape>,
+    /// Triangles covering the first shape, only present when user chose to export them
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    triangles: Vec<[Pos2; 3]>,
+}
+
+impl Document {
+    pub fn new(shapes: Vec<Shape>, triangles: Vec<[Pos2; 3]>) -> Self {
+        Self { shapes, triangles }
+    }
+
+    pub fn into_shapes(self) -> Vec<Shape> {
+        self.shapes
+    }
+
+    pub fn to_json(&self) -> Result<String, DocumentError> {
+        Ok(serde_json::to_string_pretty(self)?)
+    }
+
+    pub fn from_json(json: &str) -> Result<Self, DocumentError> {
+        Ok(serde_json::from_str(json)?)
+    }
+
+    /// Returns shapes as SVG paths, without constraints and triangles
+    pub fn to_svg(&self) -> String {
+        Svg::write(&self.shapes)
+    }
+
+    pub fn from_svg(svg: &str) -> Result<Self, DocumentError> {
+        Ok(Self::new(Svg::read(svg)?, vec![]))
+    }
+
+    /// Saves the document as SVG when the path ends with `.svg`, otherwise as JSON
+    pub fn save(&self, path: &Path) -> Result<(), DocumentError> {
+        let contents = if Self::is_svg(path) {
+            self.to_svg()
+        } else {
+            self.to_json()?
+        };
+        std::fs::write(path, contents)?;
+        Ok(())
+    }
+
+    /// Loads the document from SVG when the path ends with `.svg`, otherwise from JSON
+    pub fn load(path: &Path) -> Result<Self, DocumentError> {
+        let contents = std::fs::read_to_string(path)?;
+        if Self::is_svg(path) {
+            Self::from_svg(&contents)
+        } else {
+            Self::from_json(&contents)
+        }
+    }
+
+    pub fn is_svg(path: &Path) -> bool {
+        path.extension()
+            .is_some_and(|extension| extension.eq_ignore_ascii_case("svg"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::error::Error;
+
+    use emath::pos2;
+
+    use super::*;
+    use crate::point::{fixtures, EdgeConstraint};
+
+    #[test]
+    fn json_keeps_constraints_and_triangles() {
+        let mut outline = fixtures::points(&[(0.0, 0.0), (100.0, 0.0), (50.0, 80.0)]);
+        outline[0].apply_horizontal_constraint();
+        let triangles = vec![[pos2(0.0, 0.0), pos2(100.0, 0.0), pos2(50.0, 80.0)]];
+        let document = Document::new(vec![Shape::new(outline, vec![])], triangles.clone());
+
+        let read = Document::from_json(&document.to_json().unwrap()).unwrap();
+        assert_eq!(read.triangles, triangles);
+        let shapes = read.into_shapes();
+        assert!(matches!(
+            shapes[0].outline()[0].constraint(),
+            Some(EdgeConstraint::Horizontal)
+        ));
+    }
+
+    #[test]
+    fn errors_keep_their_cause() {
+        let error = Document::from_json("{").unwrap_err();
+        assert!(matches!(error, DocumentError::Format(_)));
+        assert!(error.source().is_some());
+        let error = Document::load(Path::new("/nonexistent/polygon.json")).unwrap_err();
+        assert!(matches!(error, DocumentError::Io(_)));
+        assert!(error.source().is_some());
+    }
+
+    #[test]
+    fn format_is_chosen_by_extension() {
+        assert!(Document::is_svg(Path::new("polygon.SVG")));
+        assert!(!Document::is_svg(Path::new("polygon.json")));
+        assert!(!Document::is_svg(Path::new("svg")));
+    }
+}
@@ -0,0 +1,282 @@
+use emath::{Pos2, Vec2};
+
+use crate::{
+    bezier::BezierData,
+    point::{ContinuityType, Point},
+    segment::EdgeSegment,
+};
+
+/// Fits cubic bezier curves to sampled strokes with Schneider's algorithm.
+///
+/// Each part of the stroke is approximated with a single cubic, whose inner control points are found
+/// with least squares for fixed tangents at its ends. If the error is too big, samples are first
+/// reparameterized with Newton's method, and then the part is split in the sample furthest from the curve.
+pub struct CurveFitting;
+
+impl CurveFitting {
+    /// Stroke is split into at least this many curves, so that the result is a proper polygon
+    const MIN_CURVES_COUNT: usize = 3;
+    /// Samples closer than this to the previous one are skipped
+    const MIN_SAMPLES_DISTANCE: f32 = 1.0;
+    const MAX_REPARAMETERIZATION_ITERATIONS: usize = 4;
+    /// Reparameterization is tried only if the error is not bigger than tolerance times this factor
+    const REPARAMETERIZATION_ERROR_FACTOR: f32 = 2.0;
+
+    /// Returns closed polygon made of bezier segments that are not further than `tolerance`
+    /// from the samples, with G1 continuity in all points. Returns None if the stroke is too short.
+    pub fn fit_closed(samples: &[Pos2], tolerance: f32) -> Option<Vec<Point>> {
+        let mut ring: Vec<Pos2> = Vec::with_capacity(samples.len());
+        for sample in samples {
+            if ring
+                .last()
+                .is_none_or(|last| last.distance(*sample) >= Self::MIN_SAMPLES_DISTANCE)
+            {
+                ring.push(*sample);
+            }
+        }
+        while ring.len() > 1 && ring[0].distance(ring[ring.len() - 1]) < Self::MIN_SAMPLES_DISTANCE
+        {
+            ring.pop();
+        }
+        if ring.len() < 2 * Self::MIN_CURVES_COUNT {
+            return None;
+        }
+
+        let samples_count = ring.len();
+        // Tangent in the sample going forward along the stroke
+        let tangent = |id: usize| {
+            let id = id % samples_count;
+            (ring[(id + 1) % samples_count] - ring[(id + samples_count - 1) % samples_count])
+                .normalized()
+        };
+        let mut cubics = vec![];
+        for part in 0..Self::MIN_CURVES_COUNT {
+            let first = part * samples_count / Self::MIN_CURVES_COUNT;
+            let last = (part + 1) * samples_count / Self::MIN_CURVES_COUNT;
+            let part_samples: Vec<Pos2> =
+                (first..=last).map(|id| ring[id % samples_count]).collect();
+            Self::fit_cubic(
+                &part_samples,
+                tangent(first),
+                -tangent(last),
+                tolerance,
+                &mut cubics,
+            );
+        }
+
+        Some(
+            cubics
+                .into_iter()
+                .map(|[start, first_inner, second_inner, _]| {
+                    Point::new_all(
+                        start,
+                        None,
+                        EdgeSegment::CubicBezier(BezierData::new([first_inner, second_inner])),
+                        ContinuityType::G1,
+                    )
+                })
+                .collect(),
+        )
+    }
+
+    /// Fits cubics to the samples, `left_tangent` points from the first sample into the curve
+    /// and `right_tangent` points from the last sample into the curve
+    fn fit_cubic(
+        samples: &[Pos2],
+        left_tangent: Vec2,
+        right_tangent: Vec2,
+        tolerance: f32,
+        cubics: &mut Vec<[Pos2; 4]>,
+    ) {
+        let (first, last) = (samples[0], samples[samples.len() - 1]);
+        if samples.len() == 2 {
+            let distance = first.distance(last) / 3.0;
+            cubics.push([
+                first,
+                first + left_tangent * distance,
+                last + right_tangent * distance,
+                last,
+            ]);
+            return;
+        }
+
+        let mut parameters = Self::chord_length_parameterize(samples);
+        let mut cubic = Self::generate_bezier(samples, &parameters, left_tangent, right_tangent);
+        let (mut max_error, mut split_id) = Self::max_error(samples, &parameters, &cubic);
+        if max_error <= tolerance {
+            cubics.push(cubic);
+            return;
+        }
+        if max_error <= tolerance * Self::REPARAMETERIZATION_ERROR_FACTOR {
+            for _ in 0..Self::MAX_REPARAMETERIZATION_ITERATIONS {
+                parameters = Self::reparameterize(samples, &parameters, &cubic);
+                cubic = Self::generate_bezier(samples, &parameters, left_tangent, right_tangent);
+                (max_error, split_id) = Self::max_error(samples, &parameters, &cubic);
+                if max_error <= tolerance {
+                    cubics.push(cubic);
+                    return;
+                }
+            }
+        }
+
+        // Tangent in the split point, pointing back along the stroke
+        let mut center_tangent = (samples[split_id - 1] - samples[split_id + 1]).normalized();
+        if !center_tangent.is_finite() || center_tangent == Vec2::ZERO {
+            center_tangent = (samples[split_id - 1] - samples[split_id]).normalized();
+        }
+        Self::fit_cubic(
+            &samples[..=split_id],
+            left_tangent,
+            center_tangent,
+            tolerance,
+            cubics,
+        );
+        Self::fit_cubic(
+            &samples[split_id..],
+            -center_tangent,
+            right_tangent,
+            tolerance,
+            cubics,
+        );
+    }
+
+    /// Returns parameters of the samples proportional to distance along the polyline connecting them
+    fn chord_length_parameterize(samples: &[Pos2]) -> Vec<f32> {
+        let mut parameters = Vec::with_capacity(samples.len());
+        let mut length = 0.0;
+        parameters.push(0.0);
+        for w in samples.windows(2) {
+            length += w[0].distance(w[1]);
+            parameters.push(length);
+        }
+        parameters
+            .iter()
+            .map(|parameter| parameter / length)
+            .collect()
+    }
+
+    /// Finds lengths of handles along the given tangents with least squares
+    fn generate_bezier(
+        samples: &[Pos2],
+        parameters: &[f32],
+        left_tangent: Vec2,
+        right_tangent: Vec2,
+    ) -> [Pos2; 4] {
+        let (first, last) = (samples[0], samples[samples.len() - 1]);
+        let mut c = [[0.0; 2]; 2];
+        let mut x = [0.0; 2];
+        for (sample, t) in samples.iter().zip(parameters) {
+            let [b0, b1, b2, b3] = Self::bernstein(*t);
+            let a = [left_tangent * b1, right_tangent * b2];
+            c[0][0] += a[0].dot(a[0]);
+            c[0][1] += a[0].dot(a[1]);
+            c[1][1] += a[1].dot(a[1]);
+            let difference = *sample - (first.to_vec2() * (b0 + b1) + last.to_vec2() * (b2 + b3));
+            x[0] += a[0].dot(difference.to_vec2());
+            x[1] += a[1].dot(difference.to_vec2());
+        }
+        c[1][0] = c[0][1];
+
+        let determinant = c[0][0] * c[1][1] - c[1][0] * c[0][1];
+        let (left_length, right_length) = if determinant.abs() > f32::EPSILON {
+            (
+                (x[0] * c[1][1] - x[1] * c[0][1]) / determinant,
+                (c[0][0] * x[1] - c[1][0] * x[0]) / determinant,
+            )
+        } else {
+            (0.0, 0.0)
+        };
+
+        // Negative or tiny handles give wrong curves, so the heuristic is used instead
+        let chord_length = first.distance(last);
+        let epsilon = 1e-6 * chord_length;
+        let (left_length, right_length) = if left_length < epsilon || right_length < epsilon {
+            (chord_length / 3.0, chord_length / 3.0)
+        } else {
+            (left_length, right_length)
+        };
+        [
+            first,
+            first + left_tangent * left_length,
+            last + right_tangent * right_length,
+            last,
+        ]
+    }
+
+    /// Improves parameters of the samples with one step of Newton's method, that looks for
+    /// the closest point of the curve
+    fn reparameterize(samples: &[Pos2], parameters: &[f32], cubic: &[Pos2; 4]) -> Vec<f32> {
+        samples
+            .iter()
+            .zip(parameters)
+            .map(|(sample, t)| {
+                let difference = BezierData::point_at(cubic, *t) - *sample;
+                let (first, second) = BezierData::derivatives_at(cubic, *t);
+                let denominator = first.dot(first) + difference.dot(second);
+                if denominator.abs() <= f32::EPSILON {
+                    return *t;
+                }
+                (t - difference.dot(first) / denominator).clamp(0.0, 1.0)
+            })
+            .collect()
+    }
+
+    /// Returns largest distance between inner samples and the curve, together with id of that sample
+    fn max_error(samples: &[Pos2], parameters: &[f32], cubic: &[Pos2; 4]) -> (f32, usize) {
+        let mut max_error = 0.0;
+        let mut split_id = samples.len() / 2;
+        for id in 1..samples.len() - 1 {
+            let error = BezierData::point_at(cubic, parameters[id]).distance(samples[id]);
+            if error > max_error {
+                max_error = error;
+                split_id = id;
+            }
+        }
+        (max_error, split_id)
+    }
+
+    fn bernstein(t: f32) -> [f32; 4] {
+        let u = 1.0 - t;
+        [u * u * u, 3.0 * u * u * t, 3.0 * u * t * t, t * t * t]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use emath::pos2;
+
+    use super::*;
+    use crate::geometry::Geometry;
+
+    #[test]
+    fn fitted_curves_follow_the_stroke() {
+        let tolerance = 1.0;
+        let samples: Vec<Pos2> = (0..120)
+            .map(|i| {
+                let angle = i as f32 / 120.0 * std::f32::consts::TAU;
+                pos2(100.0 + 60.0 * angle.cos(), 100.0 + 40.0 * angle.sin())
+            })
+            .collect();
+        let points = CurveFitting::fit_closed(&samples, tolerance).unwrap();
+        assert!(points.len() < samples.len() / 4);
+        assert!(points
+            .iter()
+            .all(|point| matches!(point.continuity_type(), ContinuityType::G1)));
+
+        let flattened = Point::flatten(&points, 0.1);
+        for sample in samples {
+            let distance = (0..flattened.len())
+                .map(|id| {
+                    let next = flattened[(id + 1) % flattened.len()];
+                    Geometry::distance_to_segment(sample, flattened[id], next)
+                })
+                .fold(f32::INFINITY, f32::min);
+            assert!(distance <= tolerance + 0.1);
+        }
+    }
+
+    #[test]
+    fn short_stroke_is_rejected() {
+        assert!(CurveFitting::fit_closed(&[pos2(0.0, 0.0), pos2(1.0, 1.0)], 1.0).is_none());
+    }
+}
@@ -0,0 +1,173 @@
+use emath::{Pos2, Vec2};
+
+/// Helpers operating on plain positions, shared by algorithms that work on flattened polygons
+pub struct Geometry;
+
+impl Geometry {
+    /// Crossings and distances smaller than this are treated as a single point
+    pub const SAME_POINT_TOLERANCE: f32 = 1.0;
+
+    pub fn cross(a: Vec2, b: Vec2) -> f32 {
+        a.x * b.y - a.y * b.x
+    }
+
+    /// Positive for rings going counterclockwise in coordinates where y grows upwards
+    pub fn signed_area(ring: &[Pos2]) -> f32 {
+        let mut area = 0.0;
+        for id in 0..ring.len() {
+            let next = ring[(id + 1) % ring.len()];
+            area += Self::cross(ring[id].to_vec2(), next.to_vec2());
+        }
+        area / 2.0
+    }
+
+    /// Checks whether point is inside area bounded by rings, using even-odd rule
+    pub fn contains_point(rings: &[Vec<Pos2>], point: Pos2) -> bool {
+        let mut inside = false;
+        for ring in rings {
+            for id in 0..ring.len() {
+                let a = ring[id];
+                let b = ring[(id + 1) % ring.len()];
+                if (a.y > point.y) != (b.y > point.y) {
+                    let x = a.x + (point.y - a.y) / (b.y - a.y) * (b.x - a.x);
+                    if point.x < x {
+                        inside = !inside;
+                    }
+                }
+            }
+        }
+        inside
+    }
+
+    /// Returns how many times the ring winds around the point (counterclockwise turns are positive)
+    pub fn winding_number(ring: &[Pos2], point: Pos2) -> i32 {
+        let mut winding_number = 0;
+        for id in 0..ring.len() {
+            let a = ring[id];
+            let b = ring[(id + 1) % ring.len()];
+            let side = Self::cross(b - a, point - a);
+            if a.y <= point.y {
+                if b.y > point.y && side > 0.0 {
+                    winding_number += 1;
+                }
+            } else if b.y <= point.y && side < 0.0 {
+                winding_number -= 1;
+            }
+        }
+        winding_number
+    }
+
+    /// Moves the point onto the closest ray going from `origin` at angle that is a multiple of `step`
+    /// (in radians), keeping its distance from the origin
+    pub fn snap_to_angle(origin: Pos2, point: Pos2, step: f32) -> Pos2 {
+        let offset = point - origin;
+        let angle = (offset.angle() / step).round() * step;
+        origin + Vec2::angled(angle) * offset.length()
+    }
+
+    /// Moves the point to the closest node of the square grid with given spacing
+    pub fn snap_to_grid(point: Pos2, step: f32) -> Pos2 {
+        ((point.to_vec2() / step).round() * step).to_pos2()
+    }
+
+    /// Moves the point to the next grid line in given direction along each axis it moves,
+    /// coordinates along the other axis are kept
+    pub fn step_on_grid(point: Pos2, direction: Vec2, step: f32) -> Pos2 {
+        let step_axis = |coordinate: f32, direction: f32| {
+            if direction > 0.0 {
+                ((coordinate / step).floor() + 1.0) * step
+            } else if direction < 0.0 {
+                ((coordinate / step).ceil() - 1.0) * step
+            } else {
+                coordinate
+            }
+        };
+        Pos2::new(
+            step_axis(point.x, direction.x),
+            step_axis(point.y, direction.y),
+        )
+    }
+
+    pub fn distance_to_line(point: Pos2, line_start: Pos2, line_end: Pos2) -> f32 {
+        let direction = line_end - line_start;
+        let length = direction.length();
+        if length <= f32::EPSILON {
+            return point.distance(line_start);
+        }
+        Self::cross(point - line_start, direction).abs() / length
+    }
+
+    pub fn distance_to_segment(point: Pos2, start: Pos2, end: Pos2) -> f32 {
+        let direction = end - start;
+        let length_squared = direction.length_sq();
+        if length_squared <= f32::EPSILON {
+            return point.distance(start);
+        }
+        let t = ((point - start).dot(direction) / length_squared).clamp(0.0, 1.0);
+        point.distance(start + direction * t)
+    }
+
+    /// Returns parameters (t along a-b, u along c-d) of intersections of segments a-b and c-d.
+    /// For collinear overlapping segments both ends of the common part are returned.
+    pub fn intersect_segments(a: Pos2, b: Pos2, c: Pos2, d: Pos2) -> Vec<(f32, f32)> {
+        const EPS: f32 = 1e-6;
+        let r = b - a;
+        let s = d - c;
+        let denominator = Self::cross(r, s);
+        let ac = c - a;
+
+        if denominator.abs() > EPS * r.length() * s.length() {
+            let t = Self::cross(ac, s) / denominator;
+            let u = Self::cross(ac, r) / denominator;
+            if (0.0..=1.0).contains(&t) && (0.0..=1.0).contains(&u) {
+                return vec![(t, u)];
+            }
+            return vec![];
+        }
+
+        // Parallel segments - only collinear ones can have common points
+        if Self::distance_to_line(c, a, b) > Self::SAME_POINT_TOLERANCE
+            || Self::distance_to_line(d, a, b) > Self::SAME_POINT_TOLERANCE
+        {
+            return vec![];
+        }
+        let r_squared = r.length_sq();
+        let s_squared = s.length_sq();
+        if r_squared <= EPS || s_squared <= EPS {
+            return vec![];
+        }
+        let t_c = ac.dot(r) / r_squared;
+        let t_d = (d - a).dot(r) / r_squared;
+        let t_min = t_c.min(t_d).max(0.0);
+        let t_max = t_c.max(t_d).min(1.0);
+        if t_min > t_max {
+            return vec![];
+        }
+        [t_min, t_max]
+            .into_iter()
+            .map(|t| {
+                let u = ((a + r * t) - c).dot(s) / s_squared;
+                (t, u.clamp(0.0, 1.0))
+            })
+            .collect()
+    }
+
+    /// Removes vertices that lie on the line between their neighbours (or duplicate the previous one)
+    pub fn remove_collinear_points(ring: &mut Vec<Pos2>, tolerance: f32) {
+        let mut id = 0;
+        while ring.len() > 3 && id < ring.len() {
+            let previous = ring[(id + ring.len() - 1) % ring.len()];
+            let next = ring[(id + 1) % ring.len()];
+            let current = ring[id];
+            let is_redundant = current.distance(previous) < f32::EPSILON
+                || (Self::distance_to_line(current, previous, next) <= tolerance
+                    && (current - previous).dot(next - current) >= 0.0);
+            if is_redundant {
+                ring.remove(id);
+                id = id.saturating_sub(1);
+            } else {
+                id += 1;
+            }
+        }
+    }
+}
@@ -0,0 +1,285 @@
+use emath::Pos2;
+
+use crate::{
+    geometry::Geometry,
+    point::{ContinuityType, Point},
+    segment::EdgeSegment,
+};
+
+pub struct ConvexHull;
+
+impl ConvexHull {
+    /// Returns convex hull of given points computed with Andrew's monotone chain algorithm.
+    /// Hull goes counterclockwise (in coordinates where y grows upwards) and contains no collinear points.
+    pub fn of_points(points: &[Pos2]) -> Vec<Pos2> {
+        Self::hull_indices(points)
+            .into_iter()
+            .map(|id| points[id])
+            .collect()
+    }
+
+    /// Returns indices of the points making the convex hull, in the same order as `of_points`.
+    /// Of points lying in the same place, the one with the lowest index is used.
+    fn hull_indices(points: &[Pos2]) -> Vec<usize> {
+        let mut sorted: Vec<usize> = (0..points.len()).collect();
+        sorted.sort_by(|&a, &b| {
+            (points[a].x.total_cmp(&points[b].x))
+                .then(points[a].y.total_cmp(&points[b].y))
+                .then(a.cmp(&b))
+        });
+        sorted.dedup_by(|a, b| points[*a] == points[*b]);
+        if sorted.len() < 3 {
+            return sorted;
+        }
+
+        let mut hull: Vec<usize> = Vec::with_capacity(sorted.len() * 2);
+        // Lower part goes left to right, upper part comes back right to left
+        for pass in [sorted.clone(), sorted.into_iter().rev().collect()] {
+            let part_start = hull.len();
+            for id in pass {
+                while hull.len() >= part_start + 2
+                    && Geometry::cross(
+                        points[hull[hull.len() - 1]] - points[hull[hull.len() - 2]],
+                        points[id] - points[hull[hull.len() - 1]],
+                    ) <= 0.0
+                {
+                    hull.pop();
+                }
+                hull.push(id);
+            }
+            // Last point of each part is the first point of the other one
+            hull.pop();
+        }
+        hull
+    }
+
+    /// Returns convex hull of all vertices and control points of the polygon curves,
+    /// which also contains the curves themselves
+    pub fn of_polygon(points: &[Point]) -> Vec<Pos2> {
+        Self::polygon_hull(points)
+            .into_iter()
+            .map(|(pos, _)| pos)
+            .collect()
+    }
+
+    /// Returns hull of the polygon together with indices of the vertices lying in each hull point,
+    /// which are empty for control points
+    fn polygon_hull(points: &[Point]) -> Vec<(Pos2, Vec<usize>)> {
+        // Vertices go first, so that their indices are kept and win over control points lying in the same place
+        let mut all_points: Vec<Pos2> = points.iter().map(|point| *point.pos()).collect();
+        for id in 0..points.len() {
+            // Curves lie inside convex hulls of their cubic control points
+            if points[id].is_start_of_curve_segment() {
+                let id_next = Point::get_next_index(points, id);
+                for control_points in points[id]
+                    .segment()
+                    .to_cubics(&points[id], &points[id_next])
+                {
+                    all_points.extend_from_slice(&control_points[1..3]);
+                }
+            }
+        }
+        Self::hull_indices(&all_points)
+            .into_iter()
+            .map(|id| {
+                let pos = all_points[id];
+                // Range is empty for control points, which come after all vertices
+                let vertices = (id..points.len())
+                    .filter(|&v| all_points[v] == pos)
+                    .collect();
+                (pos, vertices)
+            })
+            .collect()
+    }
+
+    /// Checks whether point is inside the hull or not further than `margin` from it
+    pub fn contains(hull: &[Pos2], point: Pos2, margin: f32) -> bool {
+        if hull.len() < 3 {
+            return hull
+                .iter()
+                .any(|hull_point| hull_point.distance(point) <= margin)
+                || (hull.len() == 2
+                    && Geometry::distance_to_segment(point, hull[0], hull[1]) <= margin);
+        }
+        let is_inside = (0..hull.len()).all(|id| {
+            let next = hull[(id + 1) % hull.len()];
+            Geometry::cross(next - hull[id], point - hull[id]) >= 0.0
+        });
+        is_inside
+            || (0..hull.len()).any(|id| {
+                Geometry::distance_to_segment(point, hull[id], hull[(id + 1) % hull.len()])
+                    <= margin
+            })
+    }
+
+    /// Returns polygon made of the convex hull. Edges that were already part of the hull
+    /// keep their constraints and curves. Curves bending inside the hull are replaced with lines,
+    /// so that the result stays convex. Points keep their continuity only when both their edges
+    /// were kept, other ones become G0. Curves are flattened with given tolerance to find
+    /// the direction of the polygon, control points closer than the tolerance to the hull edge
+    /// count as lying on it.
+    pub fn convexify(points: &[Point], tolerance: f32) -> Vec<Point> {
+        let mut hull = Self::polygon_hull(points);
+        if hull.len() < 3 {
+            return points.to_vec();
+        }
+        // Hull has to go in the same direction as the polygon, so that its edges can be matched
+        let polygon_area = Geometry::signed_area(&Point::flatten(points, tolerance));
+        if polygon_area < 0.0 {
+            hull.reverse();
+        }
+        let orientation = if polygon_area < 0.0 { -1.0 } else { 1.0 };
+
+        // Vertex each hull point comes from. Of vertices lying in the same place,
+        // the one starting an edge that goes to the next hull point is used
+        let originals: Vec<Option<usize>> = (0..hull.len())
+            .map(|id| {
+                let next_vertices = &hull[(id + 1) % hull.len()].1;
+                let vertices = &hull[id].1;
+                vertices
+                    .iter()
+                    .find(|&&v| next_vertices.contains(&Point::get_next_index(points, v)))
+                    .or(vertices.first())
+                    .copied()
+            })
+            .collect();
+        // Whether edge starting in each hull point is an edge of the polygon, which can be kept as it is
+        let kept_edges: Vec<bool> = (0..hull.len())
+            .map(|id| {
+                let id_next = (id + 1) % hull.len();
+                let Some(original) = originals[id] else {
+                    return false;
+                };
+                let original_next = Point::get_next_index(points, original);
+                let point = &points[original];
+                // Control points can't lie outside of the hull, so only curves lying along its edge are kept
+                hull[id_next].1.contains(&original_next)
+                    && point
+                        .segment()
+                        .to_cubics(point, &points[original_next])
+                        .iter()
+                        .flat_map(|control_points| &control_points[1..3])
+                        .all(|control_point| {
+                            Self::signed_distance_from_edge(
+                                hull[id].0,
+                                hull[id_next].0,
+                                *control_point,
+                            ) * orientation
+                                <= tolerance
+                        })
+            })
+            .collect();
+
+        (0..hull.len())
+            .map(|id| {
+                let id_previous = (id + hull.len() - 1) % hull.len();
+                let incoming_kept = kept_edges[id_previous]
+                    && originals[id_previous].is_some_and(|previous| {
+                        Some(Point::get_next_index(points, previous)) == originals[id]
+                    });
+                match originals[id] {
+                    // Continuity can't hold next to an edge that was replaced with a line
+                    Some(original) if kept_edges[id] && incoming_kept => points[original],
+                    Some(original) if kept_edges[id] => {
+                        let mut point = points[original];
+                        point.apply_G0();
+                        point
+                    }
+                    // Control point that became a vertex is a corner of the hull, like ends of replaced curves
+                    _ => Point::new_all(hull[id].0, None, EdgeSegment::Line, ContinuityType::G0),
+                }
+            })
+            .collect()
+    }
+
+    /// Signed distance of the point from the line going through the edge, positive on its left side
+    /// (in coordinates where y grows upwards)
+    fn signed_distance_from_edge(start: Pos2, end: Pos2, point: Pos2) -> f32 {
+        Geometry::cross(end - start, point - start) / start.distance(end)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use emath::{pos2, Vec2};
+
+    use super::*;
+    use crate::point::fixtures;
+
+    /// Square whose first edge is a curve bending by `bulge` to the left of it
+    /// (inside for counterclockwise polygons)
+    fn square_with_curve(bulge: f32) -> Vec<Point> {
+        let mut points = fixtures::square(0.0, 0.0, 100.0);
+        let offset = Vec2::new(0.0, bulge);
+        points[0].init_bezier_data([pos2(30.0, 0.0) + offset, pos2(70.0, 0.0) + offset]);
+        points
+    }
+
+    #[test]
+    fn convexify_replaces_inner_curves_with_lines() {
+        let hull = ConvexHull::convexify(&square_with_curve(20.0), 0.25);
+        assert_eq!(hull.len(), 4);
+        assert!(hull.iter().all(|point| !point.is_start_of_curve_segment()));
+        // Curve lying along the hull edge is kept
+        let hull = ConvexHull::convexify(&square_with_curve(0.0), 0.25);
+        assert!(hull[0].is_start_of_curve_segment());
+    }
+
+    #[test]
+    fn convexify_makes_corners_of_control_points() {
+        let hull = ConvexHull::convexify(&square_with_curve(-20.0), 0.25);
+        assert_eq!(hull.len(), 6);
+        let promoted: Vec<&Point> = hull.iter().filter(|point| point.pos().y < 0.0).collect();
+        assert_eq!(promoted.len(), 2);
+        assert!(promoted
+            .iter()
+            .all(|point| matches!(point.continuity_type(), ContinuityType::G0)));
+    }
+
+    #[test]
+    fn convexify_downgrades_points_next_to_replaced_curves() {
+        let mut points = square_with_curve(20.0);
+        points[0].apply_C1();
+        points[1].apply_C1();
+        points[2].apply_C1();
+        let hull = ConvexHull::convexify(&points, 0.25);
+        assert!(matches!(hull[0].continuity_type(), ContinuityType::G0));
+        assert!(matches!(hull[1].continuity_type(), ContinuityType::G0));
+        // Both edges of this point were kept
+        assert!(matches!(hull[2].continuity_type(), ContinuityType::C1));
+    }
+
+    #[test]
+    fn convexify_matches_edges_of_vertices_lying_in_the_same_place() {
+        let mut points = fixtures::points(&[
+            (0.0, 0.0),
+            (50.0, 50.0),
+            (0.0, 0.0),
+            (100.0, 0.0),
+            (100.0, 100.0),
+            (0.0, 100.0),
+        ]);
+        points[2].init_bezier_data([pos2(30.0, 0.0), pos2(70.0, 0.0)]);
+        let hull = ConvexHull::convexify(&points, 0.25);
+        assert_eq!(hull.len(), 4);
+        let corner = hull
+            .iter()
+            .find(|point| *point.pos() == pos2(0.0, 0.0))
+            .unwrap();
+        assert!(corner.is_start_of_curve_segment());
+    }
+
+    #[test]
+    fn hull_of_points_skips_inner_and_collinear_ones() {
+        let points = [
+            pos2(0.0, 0.0),
+            pos2(50.0, 0.0),
+            pos2(100.0, 0.0),
+            pos2(50.0, 50.0),
+            pos2(0.0, 100.0),
+        ];
+        let hull = ConvexHull::of_points(&points);
+        assert_eq!(hull.len(), 3);
+        assert!(Geometry::signed_area(&hull) > 0.0);
+    }
+}
@@ -0,0 +1,294 @@
+use emath::{Pos2, Rect};
+
+use crate::{bezier::BezierData, geometry::Geometry, point::Point};
+
+/// Segment of polygon's outline in the form used for intersection tests
+#[derive(Debug, Clone, Copy)]
+enum Segment {
+    Line([Pos2; 2]),
+    Cubic([Pos2; 4]),
+}
+
+impl Segment {
+    fn bounding_box(&self) -> Rect {
+        match self {
+            Segment::Line(p) => Rect::from_two_pos(p[0], p[1]),
+            Segment::Cubic(p) => Rect::from_points(p),
+        }
+    }
+
+    fn endpoints(&self) -> [Pos2; 2] {
+        match self {
+            Segment::Line(p) => *p,
+            Segment::Cubic(p) => [p[0], p[3]],
+        }
+    }
+
+    /// Cubic is treated as flat when both inner control points are close enough to its chord
+    fn is_flat(&self) -> bool {
+        match self {
+            Segment::Line(_) => true,
+            Segment::Cubic(p) => {
+                Geometry::distance_to_line(p[1], p[0], p[3])
+                    .max(Geometry::distance_to_line(p[2], p[0], p[3]))
+                    <= SelfIntersections::FLATNESS_TOLERANCE
+            }
+        }
+    }
+}
+
+pub struct SelfIntersections;
+
+impl SelfIntersections {
+    const FLATNESS_TOLERANCE: f32 = 0.25;
+    const MAX_SUBDIVISION_DEPTH: usize = 24;
+
+    /// Returns all points in which edges (or bezier segments) of the polygon cross each other
+    pub fn find(points: &[Point], closed: bool) -> Vec<Pos2> {
+        if points.len() < 3 {
+            return vec![];
+        }
+
+        // Each segment is stored with id of the edge it belongs to
+        // Each cubic of a curve is split into two halves, so that loops inside it are detected too
+        let mut segments: Vec<(usize, Segment)> = Vec::with_capacity(points.len() + 1);
+        for id in 0..Point::edges_count(points, closed) {
+            let id_next = Point::get_next_index(points, id);
+            if points[id].is_start_of_curve_segment() {
+                for control_points in points[id]
+                    .segment()
+                    .to_cubics(&points[id], &points[id_next])
+                {
+                    let (first, second) = BezierData::split_control_polygon(&control_points, 0.5);
+                    segments.push((id, Segment::Cubic(first)));
+                    segments.push((id, Segment::Cubic(second)));
+                }
+            } else {
+                segments.push((
+                    id,
+                    Segment::Line([*points[id].pos(), *points[id_next].pos()]),
+                ));
+            }
+        }
+
+        let boxes: Vec<Rect> = segments.iter().map(|(_, s)| s.bounding_box()).collect();
+
+        // Segments sorted by the left side of their bounding boxes are scanned left to right, and each one
+        // is tested only against earlier ones whose boxes reach it (sort and sweep of bounding boxes)
+        let mut order: Vec<usize> = (0..segments.len()).collect();
+        order.sort_by(|a, b| boxes[*a].min.x.total_cmp(&boxes[*b].min.x));
+
+        let mut intersections = vec![];
+        let mut active: Vec<usize> = vec![];
+        for current in order {
+            active.retain(|other| boxes[*other].max.x >= boxes[current].min.x);
+            for other in active.iter().copied() {
+                if !boxes[current].intersects(boxes[other]) {
+                    continue;
+                }
+                let shared_endpoint =
+                    Self::shared_endpoint(points, closed, &segments[current], &segments[other]);
+                let mut found = vec![];
+                Self::intersect_segments(&segments[current].1, &segments[other].1, 0, &mut found);
+                for point in found {
+                    if shared_endpoint.is_some_and(|shared| {
+                        shared.distance(point) < Geometry::SAME_POINT_TOLERANCE
+                    }) {
+                        continue;
+                    }
+                    Self::push_unique(&mut intersections, point);
+                }
+                if let Some(shared) = shared_endpoint {
+                    if let Some(point) =
+                        Self::backtracking_overlap(shared, &segments[current].1, &segments[other].1)
+                    {
+                        Self::push_unique(&mut intersections, point);
+                    }
+                }
+            }
+            active.push(current);
+        }
+
+        intersections
+    }
+
+    pub fn is_simple(points: &[Point], closed: bool) -> bool {
+        Self::find(points, closed).is_empty()
+    }
+
+    /// Returns position of the vertex shared by two segments, if they are adjacent on the outline.
+    /// Ends of an open path that lie in the same point (as after opening a polygon at a vertex)
+    /// are treated as shared too.
+    fn shared_endpoint(
+        points: &[Point],
+        closed: bool,
+        (first_edge, first): &(usize, Segment),
+        (second_edge, second): &(usize, Segment),
+    ) -> Option<Pos2> {
+        let [first_start, first_end] = first.endpoints();
+        let [second_start, second_end] = second.endpoints();
+        if first_edge == second_edge {
+            // Pieces of the same curve
+            if first_end == second_start {
+                return Some(first_end);
+            }
+            if second_end == first_start {
+                return Some(second_end);
+            }
+            return None;
+        }
+        if Point::get_next_index(points, *first_edge) == *second_edge && first_end == second_start {
+            return Some(first_end);
+        }
+        if Point::get_next_index(points, *second_edge) == *first_edge && second_end == first_start {
+            return Some(second_end);
+        }
+        if !closed {
+            let last_edge = Point::edges_count(points, closed) - 1;
+            let touching_ends =
+                |start: Pos2, end: Pos2| start.distance(end) < Geometry::SAME_POINT_TOLERANCE;
+            if *first_edge == 0
+                && *second_edge == last_edge
+                && touching_ends(first_start, second_end)
+            {
+                return Some(first_start);
+            }
+            if *second_edge == 0
+                && *first_edge == last_edge
+                && touching_ends(second_start, first_end)
+            {
+                return Some(second_start);
+            }
+        }
+        None
+    }
+
+    /// Adjacent segments cross only in their shared vertex, unless one of them goes back along the other.
+    /// Returns the far end of the common part of such segments, when it's longer than the tolerance.
+    fn backtracking_overlap(shared: Pos2, first: &Segment, second: &Segment) -> Option<Pos2> {
+        if !first.is_flat() || !second.is_flat() {
+            return None;
+        }
+        let far_end = |segment: &Segment| {
+            let [start, end] = segment.endpoints();
+            if start.distance(shared) < end.distance(shared) {
+                end
+            } else {
+                start
+            }
+        };
+        let (first_end, second_end) = (far_end(first), far_end(second));
+        let lies_along = |point: Pos2, end: Pos2| {
+            point.distance(shared) >= Geometry::SAME_POINT_TOLERANCE
+                && Geometry::distance_to_segment(point, shared, end)
+                    < Geometry::SAME_POINT_TOLERANCE
+        };
+        // The shorter segment ends on the longer one
+        if lies_along(second_end, first_end) {
+            Some(second_end)
+        } else if lies_along(first_end, second_end) {
+            Some(first_end)
+        } else {
+            None
+        }
+    }
+
+    /// Finds intersections of two segments by subdividing curves until they can be approximated with lines.
+    /// Parts whose bounding boxes are disjoint are clipped away on each level.
+    fn intersect_segments(first: &Segment, second: &Segment, depth: usize, out: &mut Vec<Pos2>) {
+        if !first.bounding_box().intersects(second.bounding_box()) {
+            return;
+        }
+
+        let first_flat = first.is_flat() || depth >= Self::MAX_SUBDIVISION_DEPTH;
+        let second_flat = second.is_flat() || depth >= Self::MAX_SUBDIVISION_DEPTH;
+
+        if first_flat && second_flat {
+            let [a, b] = first.endpoints();
+            let [c, d] = second.endpoints();
+            for (t, _) in Geometry::intersect_segments(a, b, c, d) {
+                Self::push_unique(out, a.lerp(b, t));
+            }
+            return;
+        }
+
+        // Split the bigger one (or the only one that is still curved)
+        let split_first = !first_flat
+            && (second_flat
+                || first.bounding_box().size().length() >= second.bounding_box().size().length());
+        if split_first {
+            if let Segment::Cubic(p) = first {
+                let (a, b) = BezierData::split_control_polygon(p, 0.5);
+                Self::intersect_segments(&Segment::Cubic(a), second, depth + 1, out);
+                Self::intersect_segments(&Segment::Cubic(b), second, depth + 1, out);
+            }
+        } else if let Segment::Cubic(p) = second {
+            let (a, b) = BezierData::split_control_polygon(p, 0.5);
+            Self::intersect_segments(first, &Segment::Cubic(a), depth + 1, out);
+            Self::intersect_segments(first, &Segment::Cubic(b), depth + 1, out);
+        }
+    }
+
+    fn push_unique(points: &mut Vec<Pos2>, point: Pos2) {
+        if points
+            .iter()
+            .all(|p| p.distance(point) >= Geometry::SAME_POINT_TOLERANCE)
+        {
+            points.push(point);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use emath::pos2;
+
+    use super::*;
+    use crate::point::fixtures::points as polygon;
+
+    #[test]
+    fn find_reports_crossing_edges() {
+        let bow_tie = polygon(&[(0.0, 0.0), (50.0, 50.0), (50.0, 0.0), (0.0, 50.0)]);
+        let intersections = SelfIntersections::find(&bow_tie, true);
+        assert_eq!(intersections.len(), 1);
+        assert!(intersections[0].distance(pos2(25.0, 25.0)) < 0.01);
+    }
+
+    #[test]
+    fn find_ignores_shared_vertices() {
+        let square = polygon(&[(0.0, 0.0), (100.0, 0.0), (100.0, 100.0), (0.0, 100.0)]);
+        assert!(SelfIntersections::is_simple(&square, true));
+        // Path opened in a vertex starts and ends in the same place
+        let opened = polygon(&[
+            (0.0, 0.0),
+            (100.0, 0.0),
+            (100.0, 100.0),
+            (0.0, 100.0),
+            (0.0, 0.0),
+        ]);
+        assert!(SelfIntersections::is_simple(&opened, false));
+    }
+
+    #[test]
+    fn find_detects_loop_of_a_curve() {
+        let mut points = polygon(&[(0.0, 0.0), (100.0, 0.0), (50.0, 100.0)]);
+        // Control points swapped across the edge make the curve cross itself
+        points[0].init_bezier_data([pos2(150.0, -50.0), pos2(-50.0, -50.0)]);
+        assert_eq!(SelfIntersections::find(&points, true).len(), 1);
+    }
+
+    #[test]
+    fn find_detects_edge_going_back_along_previous_one() {
+        // Third vertex lies (almost) on the first edge, so the second edge runs back over it
+        for turn in [pos2(50.0, 0.0), pos2(50.0, 0.5)] {
+            let points = [pos2(0.0, 0.0), pos2(100.0, 0.0), turn, pos2(0.0, 100.0)]
+                .into_iter()
+                .map(Point::new)
+                .collect::<Vec<_>>();
+            let intersections = SelfIntersections::find(&points, true);
+            assert!(intersections
+                .iter()
+                .any(|point| point.distance(turn) < 0.01));
+        }
+    }
+}
@@ -0,0 +1,25 @@
+//! Polygons made of straight edges and curves, with constraints on the edges and continuity in the
+//! vertices kept while points are moved, together with algorithms working on them.
+//!
+//! Nothing here depends on the GUI, positions are plain [`Pos2`] and [`Vec2`] from `emath`.
+
+pub mod bezier;
+pub mod clipping;
+pub mod corners;
+pub mod curvature;
+pub mod document;
+pub mod fitting;
+pub mod geometry;
+pub mod hull;
+pub mod intersections;
+pub mod offset;
+pub mod point;
+pub mod raster;
+pub mod segment;
+pub mod shape;
+pub mod simplification;
+pub mod spline;
+pub mod svg;
+pub mod triangulation;
+
+pub use emath::{Pos2, Rect, Vec2};
@@ -0,0 +1,570 @@
+use emath::{Pos2, Rect, Vec2};
+
+use crate::{
+    bezier::BezierData,
+    clipping::{BooleanOperation, Clipper},
+    geometry::Geometry,
+    intersections::SelfIntersections,
+    point::{ContinuityType, Point},
+    segment::EdgeSegment,
+    shape::Shape,
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum JoinStyle {
+    Miter,
+    Round,
+    Bevel,
+}
+
+impl JoinStyle {
+    pub const ALL: [JoinStyle; 3] = [JoinStyle::Miter, JoinStyle::Round, JoinStyle::Bevel];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            JoinStyle::Miter => "Miter",
+            JoinStyle::Round => "Round",
+            JoinStyle::Bevel => "Bevel",
+        }
+    }
+}
+
+/// Part of the offset outline
+#[derive(Debug, Clone, Copy)]
+enum Piece {
+    Line([Pos2; 2]),
+    Cubic([Pos2; 4]),
+}
+
+impl Piece {
+    fn start(&self) -> Pos2 {
+        match self {
+            Piece::Line(p) => p[0],
+            Piece::Cubic(p) => p[0],
+        }
+    }
+
+    fn end(&self) -> Pos2 {
+        match self {
+            Piece::Line(p) => p[1],
+            Piece::Cubic(p) => p[3],
+        }
+    }
+
+    fn start_tangent(&self) -> Vec2 {
+        match self {
+            Piece::Line(p) => p[1] - p[0],
+            Piece::Cubic(p) => PolygonOffset::cubic_start_tangent(p),
+        }
+    }
+
+    fn end_tangent(&self) -> Vec2 {
+        match self {
+            Piece::Line(p) => p[1] - p[0],
+            Piece::Cubic(p) => PolygonOffset::cubic_end_tangent(p),
+        }
+    }
+
+    /// Returns control points of cubic curve describing the piece, for lines parametrization stays linear
+    fn to_cubic(self) -> [Pos2; 4] {
+        match self {
+            Piece::Line([a, b]) => [a, a.lerp(b, 1.0 / 3.0), a.lerp(b, 2.0 / 3.0), b],
+            Piece::Cubic(p) => p,
+        }
+    }
+
+    fn split(self, t: f32) -> (Piece, Piece) {
+        match self {
+            Piece::Line([a, b]) => {
+                let middle = a.lerp(b, t);
+                (Piece::Line([a, middle]), Piece::Line([middle, b]))
+            }
+            Piece::Cubic(p) => {
+                let (left, right) = BezierData::split_control_polygon(&p, t);
+                (Piece::Cubic(left), Piece::Cubic(right))
+            }
+        }
+    }
+}
+
+/// Creates outlines placed in constant distance from the polygon.
+///
+/// Every edge is moved along its normal (bezier segments are approximated with new cubic curves),
+/// gaps at convex corners are filled according to the join style and the ends meeting at concave corners
+/// are connected through the original vertex. If that produces self-intersections, the outline is flattened
+/// and only the parts bounding the area with positive winding number are kept.
+pub struct PolygonOffset;
+
+impl PolygonOffset {
+    /// Maximal distance between offset bezier segment and its approximation
+    const TOLERANCE: f32 = 0.5;
+    const MAX_SUBDIVISION_DEPTH: usize = 6;
+
+    /// Returns shapes created by moving the boundary of the shape by `distance` (positive grows the shape,
+    /// negative shrinks it). Holes move the other way and are cut out of the offset outline, which is flattened
+    /// with given tolerance when it has to be cut. Open paths have no inside, so they give no shapes.
+    pub fn apply(
+        shape: &Shape,
+        distance: f32,
+        join_style: JoinStyle,
+        miter_limit: f32,
+        tolerance: f32,
+    ) -> Vec<Shape> {
+        if !shape.is_closed() {
+            return vec![];
+        }
+        let outline = Self::offset_ring(
+            shape.outline(),
+            distance,
+            join_style,
+            miter_limit,
+            tolerance,
+        );
+        shape.holes().iter().fold(outline, |shapes, hole| {
+            // Growing the shape shrinks its holes
+            let hole_shapes =
+                Self::offset_ring(hole, -distance, join_style, miter_limit, tolerance);
+            hole_shapes.iter().fold(shapes, |shapes, hole_shape| {
+                shapes
+                    .iter()
+                    .flat_map(|shape| {
+                        Clipper::apply(shape, hole_shape, BooleanOperation::Difference, tolerance)
+                    })
+                    .collect()
+            })
+        })
+    }
+
+    /// Returns shapes created by moving the closed ring by `distance` (positive grows it, negative shrinks it)
+    fn offset_ring(
+        points: &[Point],
+        distance: f32,
+        join_style: JoinStyle,
+        miter_limit: f32,
+        tolerance: f32,
+    ) -> Vec<Shape> {
+        let flattened = Point::flatten(points, tolerance);
+        let orientation = Geometry::signed_area(&flattened).signum();
+        if points.len() < 3 || orientation == 0.0 || distance == 0.0 {
+            return vec![];
+        }
+
+        // Outward normal is on the right side of edges of counterclockwise polygons and on the left otherwise
+        let normal =
+            |tangent: Vec2| Vec2::new(tangent.y, -tangent.x).normalized() * orientation * distance;
+
+        let segments: Vec<Piece> = (0..points.len())
+            .flat_map(|id| {
+                let id_next = Point::get_next_index(points, id);
+                if points[id].is_start_of_curve_segment() {
+                    points[id]
+                        .segment()
+                        .to_cubics(&points[id], &points[id_next])
+                        .into_iter()
+                        .map(Piece::Cubic)
+                        .collect()
+                } else if points[id].pos().distance(*points[id_next].pos()) > f32::EPSILON {
+                    vec![Piece::Line([*points[id].pos(), *points[id_next].pos()])]
+                } else {
+                    vec![]
+                }
+            })
+            .collect();
+
+        let mut offset_segments: Vec<Vec<Piece>> = segments
+            .iter()
+            .map(|segment| match segment {
+                Piece::Line([a, b]) => {
+                    let n = normal(*b - *a);
+                    vec![Piece::Line([*a + n, *b + n])]
+                }
+                Piece::Cubic(p) => {
+                    let mut pieces = vec![];
+                    Self::offset_cubic(p, &normal, 0, &mut pieces);
+                    pieces
+                }
+            })
+            .collect();
+
+        let mut joins: Vec<Vec<Piece>> = vec![vec![]; segments.len()];
+        for id in 0..segments.len() {
+            let id_next = (id + 1) % segments.len();
+            let vertex = segments[id].end();
+            let incoming = segments[id].end_tangent();
+            let outgoing = segments[id_next].start_tangent();
+            let end = offset_segments[id].last().unwrap().end();
+            let start = offset_segments[id_next].first().unwrap().start();
+            if end.distance(start) <= f32::EPSILON {
+                continue;
+            }
+
+            let is_outer_corner =
+                Geometry::cross(incoming, outgoing) * orientation * distance.signum() > 0.0
+                    || incoming.dot(outgoing) < 0.0
+                        && Geometry::cross(incoming, outgoing).abs() <= f32::EPSILON;
+            if is_outer_corner {
+                joins[id] = Self::outer_join(vertex, end, start, join_style, miter_limit);
+                continue;
+            }
+
+            // Inner corner - pieces can be just cut in the point where they cross
+            let incoming_piece = *offset_segments[id].last().unwrap();
+            let outgoing_piece = *offset_segments[id_next].first().unwrap();
+            match Self::find_crossing(
+                &incoming_piece.to_cubic(),
+                (0.0, 1.0),
+                &outgoing_piece.to_cubic(),
+                (0.0, 1.0),
+                0,
+            ) {
+                Some((t, u)) if id != id_next => {
+                    *offset_segments[id].last_mut().unwrap() = incoming_piece.split(t).0;
+                    let outgoing_piece = *offset_segments[id_next].first().unwrap();
+                    *offset_segments[id_next].first_mut().unwrap() = outgoing_piece.split(u).1;
+                }
+                _ => joins[id] = vec![Piece::Line([end, vertex]), Piece::Line([vertex, start])],
+            }
+        }
+
+        let pieces: Vec<Piece> = offset_segments
+            .into_iter()
+            .zip(joins)
+            .flat_map(|(segment, join)| segment.into_iter().chain(join))
+            .collect();
+        let outline = Self::pieces_to_points(&pieces);
+
+        let outline_flattened = Point::flatten(&outline, tolerance);
+        let same_orientation = Geometry::signed_area(&outline_flattened).signum() == orientation;
+        if same_orientation && SelfIntersections::is_simple(&outline, true) {
+            return vec![Shape::new(outline, vec![])];
+        }
+
+        // Loops created by the offset have winding number of the opposite sign (or zero), so they are cut off
+        Clipper::extract_region(std::slice::from_ref(&outline_flattened), |pos| {
+            Geometry::winding_number(&outline_flattened, pos) as f32 * orientation > 0.0
+        })
+    }
+
+    fn outer_join(
+        vertex: Pos2,
+        end: Pos2,
+        start: Pos2,
+        join_style: JoinStyle,
+        miter_limit: f32,
+    ) -> Vec<Piece> {
+        let radius = end.distance(vertex);
+        let n1 = (end - vertex) / radius;
+        let n2 = (start - vertex) / radius;
+        match join_style {
+            JoinStyle::Miter => {
+                let cos_sum = 1.0 + n1.dot(n2);
+                // Length of the miter divided by the distance is 1 / cos(angle / 2) = sqrt(2 / (1 + cos(angle)))
+                if cos_sum <= f32::EPSILON || (2.0 / cos_sum).sqrt() > miter_limit {
+                    return vec![Piece::Line([end, start])];
+                }
+                let miter = vertex + (n1 + n2) * radius / cos_sum;
+                vec![Piece::Line([end, miter]), Piece::Line([miter, start])]
+            }
+            JoinStyle::Round => Self::arc(vertex, end, start),
+            JoinStyle::Bevel => vec![Piece::Line([end, start])],
+        }
+    }
+
+    /// Approximates shorter circular arc around `center` going from `from` to `to` with cubic curves
+    fn arc(center: Pos2, from: Pos2, to: Pos2) -> Vec<Piece> {
+        let radius = from.distance(center);
+        let start_angle = (from.y - center.y).atan2(from.x - center.x);
+        let sweep =
+            Geometry::cross(from - center, to - center).atan2((from - center).dot(to - center));
+        let parts_count = (sweep.abs() / std::f32::consts::FRAC_PI_2).ceil().max(1.0) as usize;
+        let part_sweep = sweep / parts_count as f32;
+        // Distance of inner control points from the ends for arc of given angle
+        let handle = 4.0 / 3.0 * (part_sweep / 4.0).tan() * radius;
+
+        (0..parts_count)
+            .map(|part| {
+                let a0 = start_angle + part_sweep * part as f32;
+                let a1 = a0 + part_sweep;
+                let p0 = if part == 0 {
+                    from
+                } else {
+                    center + radius * Vec2::angled(a0)
+                };
+                let p3 = if part + 1 == parts_count {
+                    to
+                } else {
+                    center + radius * Vec2::angled(a1)
+                };
+                // Tangent of the circle points a quarter turn further than the radius
+                let p1 = p0 + handle * Vec2::angled(a0 + std::f32::consts::FRAC_PI_2);
+                let p2 = p3 - handle * Vec2::angled(a1 + std::f32::consts::FRAC_PI_2);
+                Piece::Cubic([p0, p1, p2, p3])
+            })
+            .collect()
+    }
+
+    /// Approximates offset of the cubic curve with moved control polygon (Tiller-Hanson method),
+    /// subdividing the curve as long as the approximation is not precise enough
+    fn offset_cubic(
+        control_points: &[Pos2; 4],
+        normal: &impl Fn(Vec2) -> Vec2,
+        depth: usize,
+        out: &mut Vec<Piece>,
+    ) {
+        let [p0, p1, p2, p3] = *control_points;
+        let start_normal = normal(Self::cubic_start_tangent(control_points));
+        let end_normal = normal(Self::cubic_end_tangent(control_points));
+        let middle_normal = if (p2 - p1).length() > f32::EPSILON {
+            normal(p2 - p1)
+        } else {
+            (start_normal + end_normal) / 2.0
+        };
+
+        let q0 = p0 + start_normal;
+        let q3 = p3 + end_normal;
+        let q1 = Self::lines_crossing(q0, p1 - p0, p1 + middle_normal, p2 - p1)
+            .unwrap_or(p1 + start_normal);
+        let q2 = Self::lines_crossing(q3, p3 - p2, p2 + middle_normal, p2 - p1)
+            .unwrap_or(p2 + end_normal);
+        let offset_curve = [q0, q1, q2, q3];
+
+        let is_precise_enough = [0.25, 0.5, 0.75].into_iter().all(|t| {
+            let (left, right) = BezierData::split_control_polygon(control_points, t);
+            let tangent = left[3] - left[2] + (right[1] - right[0]);
+            let expected = left[3] + normal(tangent);
+            let (approximated, _) = BezierData::split_control_polygon(&offset_curve, t);
+            approximated[3].distance(expected) <= Self::TOLERANCE
+        });
+
+        if is_precise_enough || depth >= Self::MAX_SUBDIVISION_DEPTH {
+            out.push(Piece::Cubic(offset_curve));
+        } else {
+            let (left, right) = BezierData::split_control_polygon(control_points, 0.5);
+            Self::offset_cubic(&left, normal, depth + 1, out);
+            Self::offset_cubic(&right, normal, depth + 1, out);
+        }
+    }
+
+    /// Returns parameters of the crossing of two curves that is the closest to the end of the first one.
+    /// Parts of the curves are subdivided as long as their bounding boxes overlap.
+    fn find_crossing(
+        first: &[Pos2; 4],
+        first_range: (f32, f32),
+        second: &[Pos2; 4],
+        second_range: (f32, f32),
+        depth: usize,
+    ) -> Option<(f32, f32)> {
+        const MAX_DEPTH: usize = 20;
+        if !Rect::from_points(first).intersects(Rect::from_points(second)) {
+            return None;
+        }
+        let is_flat = |p: &[Pos2; 4]| {
+            Geometry::distance_to_line(p[1], p[0], p[3])
+                .max(Geometry::distance_to_line(p[2], p[0], p[3]))
+                <= 0.1
+        };
+        if depth >= MAX_DEPTH || (is_flat(first) && is_flat(second)) {
+            return Geometry::intersect_segments(first[0], first[3], second[0], second[3])
+                .into_iter()
+                .map(|(t, u)| {
+                    (
+                        first_range.0 + (first_range.1 - first_range.0) * t,
+                        second_range.0 + (second_range.1 - second_range.0) * u,
+                    )
+                })
+                .max_by(|a, b| a.0.total_cmp(&b.0));
+        }
+
+        let (first_left, first_right) = BezierData::split_control_polygon(first, 0.5);
+        let (second_left, second_right) = BezierData::split_control_polygon(second, 0.5);
+        let first_middle = (first_range.0 + first_range.1) / 2.0;
+        let second_middle = (second_range.0 + second_range.1) / 2.0;
+        // Parts closer to the end of the first curve are checked first
+        for (first_part, first_part_range) in [
+            (first_right, (first_middle, first_range.1)),
+            (first_left, (first_range.0, first_middle)),
+        ] {
+            for (second_part, second_part_range) in [
+                (second_left, (second_range.0, second_middle)),
+                (second_right, (second_middle, second_range.1)),
+            ] {
+                let crossing = Self::find_crossing(
+                    &first_part,
+                    first_part_range,
+                    &second_part,
+                    second_part_range,
+                    depth + 1,
+                );
+                if crossing.is_some() {
+                    return crossing;
+                }
+            }
+        }
+        None
+    }
+
+    /// Returns crossing of lines going through points a and b in given directions
+    fn lines_crossing(a: Pos2, a_direction: Vec2, b: Pos2, b_direction: Vec2) -> Option<Pos2> {
+        let denominator = Geometry::cross(a_direction, b_direction);
+        if denominator.abs() <= 1e-3 * a_direction.length() * b_direction.length() {
+            return None;
+        }
+        let t = Geometry::cross(b - a, b_direction) / denominator;
+        Some(a + a_direction * t)
+    }
+
+    fn cubic_start_tangent(p: &[Pos2; 4]) -> Vec2 {
+        [p[1], p[2], p[3]]
+            .into_iter()
+            .map(|q| q - p[0])
+            .find(|v| v.length() > f32::EPSILON)
+            .unwrap_or(Vec2::X)
+    }
+
+    fn cubic_end_tangent(p: &[Pos2; 4]) -> Vec2 {
+        [p[2], p[1], p[0]]
+            .into_iter()
+            .map(|q| p[3] - q)
+            .find(|v| v.length() > f32::EPSILON)
+            .unwrap_or(Vec2::X)
+    }
+
+    /// Creates polygon from connected pieces. Points where pieces meet smoothly get G1 continuity.
+    fn pieces_to_points(pieces: &[Piece]) -> Vec<Point> {
+        // Consecutive lines going in the same direction are merged into one edge
+        let mut merged: Vec<Piece> = vec![];
+        for piece in pieces {
+            if piece.start().distance(piece.end()) <= f32::EPSILON {
+                continue;
+            }
+            if let (Some(Piece::Line([a, b])), Piece::Line([_, c])) = (merged.last(), piece) {
+                if Geometry::distance_to_line(*b, *a, *c) <= 1e-3 && (*b - *a).dot(*c - *b) > 0.0 {
+                    *merged.last_mut().unwrap() = Piece::Line([*a, *c]);
+                    continue;
+                }
+            }
+            merged.push(*piece);
+        }
+        if let (Some(Piece::Line([a, b])), Some(Piece::Line([_, c]))) =
+            (merged.last().copied(), merged.first().copied())
+        {
+            if merged.len() > 3
+                && Geometry::distance_to_line(b, a, c) <= 1e-3
+                && (b - a).dot(c - b) > 0.0
+            {
+                merged.pop();
+                merged[0] = Piece::Line([a, c]);
+            }
+        }
+        let pieces = merged;
+        (0..pieces.len())
+            .map(|id| {
+                let previous = pieces[(id + pieces.len() - 1) % pieces.len()];
+                let current = pieces[id];
+                let incoming = previous.end_tangent();
+                let outgoing = current.start_tangent();
+                let is_smooth = incoming.dot(outgoing) > 0.0
+                    && Geometry::cross(incoming.normalized(), outgoing.normalized()).abs() < 1e-3;
+                let continuity_type = if is_smooth {
+                    ContinuityType::G1
+                } else {
+                    ContinuityType::G0
+                };
+                let segment = match current {
+                    Piece::Line(_) => EdgeSegment::Line,
+                    Piece::Cubic(p) => EdgeSegment::CubicBezier(BezierData::new([p[1], p[2]])),
+                };
+                Point::new_all(current.start(), None, segment, continuity_type)
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::point::fixtures::{self, area};
+
+    fn square() -> Vec<Point> {
+        fixtures::square(0.0, 0.0, 100.0)
+    }
+
+    #[test]
+    fn miter_offset_of_square_is_square() {
+        for points in [square(), square().into_iter().rev().collect()] {
+            let grown = PolygonOffset::apply(
+                &Shape::new(points.clone(), vec![]),
+                10.0,
+                JoinStyle::Miter,
+                4.0,
+                0.5,
+            );
+            assert!((area(&grown) - 120.0 * 120.0).abs() < 1.0);
+            let shrunk = PolygonOffset::apply(
+                &Shape::new(points.clone(), vec![]),
+                -10.0,
+                JoinStyle::Miter,
+                4.0,
+                0.5,
+            );
+            assert!((area(&shrunk) - 80.0 * 80.0).abs() < 1.0);
+        }
+    }
+
+    #[test]
+    fn round_and_bevel_joins_cut_corners() {
+        let round = area(&PolygonOffset::apply(
+            &Shape::new(square(), vec![]),
+            10.0,
+            JoinStyle::Round,
+            4.0,
+            0.5,
+        ));
+        let bevel = area(&PolygonOffset::apply(
+            &Shape::new(square(), vec![]),
+            10.0,
+            JoinStyle::Bevel,
+            4.0,
+            0.5,
+        ));
+        let round_expected = 100.0 * 100.0 + 4.0 * 100.0 * 10.0 + std::f32::consts::PI * 100.0;
+        assert!((round - round_expected).abs() < 5.0);
+        assert!((bevel - (120.0 * 120.0 - 4.0 * 50.0)).abs() < 1.0);
+    }
+
+    #[test]
+    fn shrinking_too_much_leaves_nothing() {
+        assert!(PolygonOffset::apply(
+            &Shape::new(square(), vec![]),
+            -60.0,
+            JoinStyle::Miter,
+            4.0,
+            0.5
+        )
+        .is_empty());
+    }
+
+    #[test]
+    fn holes_shrink_when_shape_grows() {
+        let hole: Vec<Point> = fixtures::square(40.0, 40.0, 20.0)
+            .into_iter()
+            .rev()
+            .collect();
+        let grown = PolygonOffset::apply(
+            &Shape::new(square(), vec![hole]),
+            5.0,
+            JoinStyle::Miter,
+            4.0,
+            0.5,
+        );
+        assert_eq!(grown.len(), 1);
+        assert_eq!(grown[0].holes().len(), 1);
+        assert!((area(&grown) - (110.0 * 110.0 - 10.0 * 10.0)).abs() < 1.0);
+    }
+
+    #[test]
+    fn open_path_has_no_offset() {
+        let path = Shape::new_open(square());
+        assert!(PolygonOffset::apply(&path, 10.0, JoinStyle::Miter, 4.0, 0.5).is_empty());
+    }
+}
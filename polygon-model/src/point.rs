@@ -0,0 +1,1529 @@
+use std::fmt;
+
+use emath::{Pos2, Vec2};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    bezier::BezierData,
+    geometry::Geometry,
+    segment::{ArcData, EdgeSegment},
+};
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub enum EdgeConstraint {
+    Horizontal,
+    Vertical,
+    ConstWidth(i32),
+    /// Length of the curve measured along it, curved counterpart of `ConstWidth`
+    ConstLength(i32),
+}
+
+impl EdgeConstraint {
+    pub fn name(&self) -> &'static str {
+        match self {
+            EdgeConstraint::Horizontal => "horizontal constraint",
+            EdgeConstraint::Vertical => "vertical constraint",
+            EdgeConstraint::ConstWidth(_) => "constant width constraint",
+            EdgeConstraint::ConstLength(_) => "constant length constraint",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ContinuityType {
+    G0,
+    C1,
+    G1,
+    /// Equal first and second derivatives, only between two cubic bezier segments
+    C2,
+    /// Equal tangents and curvatures, only between two cubic bezier segments
+    G2,
+}
+
+/// Reason why a constraint or continuity can't be applied
+#[derive(Debug, Clone, Copy)]
+pub enum EditError {
+    /// Edge already has a constraint or is a curve
+    EdgeNotFree,
+    /// Neighbouring edge has the same horizontal or vertical constraint
+    NeighbourHasSameConstraint(EdgeConstraint),
+    /// Point doesn't join a curve with another edge, or (for C2 and G2) two cubic curves
+    ContinuityNotApplicable,
+}
+
+impl fmt::Display for EditError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            EditError::EdgeNotFree => {
+                write!(
+                    f,
+                    "Only straight edges without constraint can get a new one"
+                )
+            }
+            EditError::NeighbourHasSameConstraint(constraint) => {
+                write!(f, "Neighbouring edge already has {}", constraint.name())
+            }
+            EditError::ContinuityNotApplicable => {
+                write!(f, "Continuity can't be applied in this point")
+            }
+        }
+    }
+}
+
+impl std::error::Error for EditError {}
+
+#[derive(Debug, Clone, Copy)]
+enum UpdateDirection {
+    Left,
+    Right,
+}
+
+// Each point is at the same time start of some edge
+// Information about this edge are stored in this struct
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct Point {
+    pos: Pos2,
+    /// Contraint that is applied to edge which starts in this point (and ends in the next one)
+    constraint: Option<EdgeConstraint>,
+    /// Geometry of the edge which starts in this point (and ends in the next one)
+    #[serde(
+        alias = "bezier_data",
+        deserialize_with = "EdgeSegment::deserialize_stored"
+    )]
+    segment: EdgeSegment,
+    continuity_type: ContinuityType,
+}
+
+impl Point {
+    /// Distance of new curves from the straight edge they replace
+    const INITIAL_CURVE_OFFSET: f32 = 20.0;
+    /// Limit of steps when looking for position of the curve end that keeps its length
+    const MAX_LENGTH_SEARCH_ITERATIONS: usize = 32;
+
+    pub fn new(pos: Pos2) -> Self {
+        Self {
+            pos,
+            constraint: None,
+            segment: EdgeSegment::Line,
+            continuity_type: ContinuityType::C1,
+        }
+    }
+
+    pub fn new_all(
+        pos: Pos2,
+        edge_constraint: Option<EdgeConstraint>,
+        segment: EdgeSegment,
+        continuity_type: ContinuityType,
+    ) -> Self {
+        Self {
+            pos,
+            constraint: edge_constraint,
+            segment,
+            continuity_type,
+        }
+    }
+
+    pub fn pos(&self) -> &Pos2 {
+        &self.pos
+    }
+
+    pub fn pos_mut(&mut self) -> &mut Pos2 {
+        &mut self.pos
+    }
+
+    pub fn constraint(&self) -> &Option<EdgeConstraint> {
+        &self.constraint
+    }
+
+    pub fn segment(&self) -> &EdgeSegment {
+        &self.segment
+    }
+
+    pub fn segment_mut(&mut self) -> &mut EdgeSegment {
+        &mut self.segment
+    }
+
+    pub fn has_constraint(&self) -> bool {
+        self.constraint.is_some()
+    }
+
+    pub fn has_horizontal_constraint(&self) -> bool {
+        match self.constraint() {
+            Some(res) => matches!(res, EdgeConstraint::Horizontal),
+            None => false,
+        }
+    }
+
+    pub fn has_vertical_constraint(&self) -> bool {
+        match self.constraint() {
+            Some(res) => matches!(res, EdgeConstraint::Vertical),
+            None => false,
+        }
+    }
+
+    pub fn is_start_of_curve_segment(&self) -> bool {
+        self.segment.is_curve()
+    }
+
+    pub fn is_end_of_curve_segment(points: &[Point], closed: bool, point_index: usize) -> bool {
+        (closed || point_index != 0)
+            && points[Point::get_previous_index(points, point_index)].is_start_of_curve_segment()
+    }
+
+    pub fn is_part_of_curve_segment(points: &[Point], closed: bool, point_index: usize) -> bool {
+        Self::is_end_of_curve_segment(points, closed, point_index)
+            || points[point_index].is_start_of_curve_segment()
+    }
+
+    /// Checks whether the point is the first or the last one of an open path,
+    /// such points have only one edge and no continuity
+    pub fn is_endpoint(points: &[Point], closed: bool, point_index: usize) -> bool {
+        !closed && (point_index == 0 || point_index + 1 == points.len())
+    }
+
+    /// Returns number of edges, open path has no edge between its last and first point
+    pub fn edges_count(points: &[Point], closed: bool) -> usize {
+        if closed {
+            points.len()
+        } else {
+            points.len().saturating_sub(1)
+        }
+    }
+
+    pub fn init_bezier_data(&mut self, initial_pos: [Pos2; 2]) {
+        self.segment = EdgeSegment::CubicBezier(BezierData::new(initial_pos));
+    }
+
+    pub fn init_quadratic_bezier_data(&mut self, initial_pos: Pos2) {
+        self.segment = EdgeSegment::QuadraticBezier(initial_pos);
+    }
+
+    pub fn init_arc_data(&mut self, sweep: f32) {
+        self.segment = EdgeSegment::CircularArc(ArcData::new(sweep));
+    }
+
+    /// Changes the edge that starts in this point back into a straight line
+    pub fn remove_curve(&mut self) {
+        self.segment = EdgeSegment::Line;
+        // Constant length is applied only to curves
+        if let Some(EdgeConstraint::ConstLength(_)) = self.constraint {
+            self.remove_constraint();
+        }
+    }
+
+    pub fn continuity_type(&self) -> &ContinuityType {
+        &self.continuity_type
+    }
+
+    #[allow(non_snake_case)]
+    pub fn apply_G0(&mut self) {
+        self.continuity_type = ContinuityType::G0;
+    }
+
+    #[allow(non_snake_case)]
+    pub fn apply_G1(&mut self) {
+        self.continuity_type = ContinuityType::G1;
+    }
+
+    #[allow(non_snake_case)]
+    pub fn apply_C1(&mut self) {
+        self.continuity_type = ContinuityType::C1;
+    }
+
+    #[allow(non_snake_case)]
+    pub fn apply_C2(&mut self) {
+        self.continuity_type = ContinuityType::C2;
+    }
+
+    #[allow(non_snake_case)]
+    pub fn apply_G2(&mut self) {
+        self.continuity_type = ContinuityType::G2;
+    }
+
+    /// Returns true if both edges adjacent to the point are cubic bezier segments,
+    /// which is required for C2 and G2 continuity
+    pub fn joins_cubic_segments(points: &[Point], point_index: usize) -> bool {
+        let previous_index = Self::get_previous_index(points, point_index);
+        matches!(points[previous_index].segment, EdgeSegment::CubicBezier(_))
+            && matches!(points[point_index].segment, EdgeSegment::CubicBezier(_))
+    }
+
+    pub fn remove_constraint(&mut self) {
+        self.constraint = None;
+    }
+
+    pub fn apply_horizontal_constraint(&mut self) {
+        self.constraint = Some(EdgeConstraint::Horizontal);
+    }
+
+    pub fn apply_vertical_constraint(&mut self) {
+        self.constraint = Some(EdgeConstraint::Vertical);
+    }
+
+    pub fn apply_width_constraint(&mut self, width: i32) {
+        self.constraint = Some(EdgeConstraint::ConstWidth(width));
+    }
+
+    pub fn apply_length_constraint(&mut self, length: i32) {
+        self.constraint = Some(EdgeConstraint::ConstLength(length));
+    }
+
+    /// Applies constraint to the edge starting in the point and moves its neighbours to keep it.
+    /// Only straight edges without constraint can get one, and two neighbouring edges can't be both
+    /// horizontal or vertical.
+    pub fn constrain_edge(
+        points: &mut [Point],
+        closed: bool,
+        edge_start_index: usize,
+        constraint: EdgeConstraint,
+    ) -> Result<(), EditError> {
+        let point = &points[edge_start_index];
+        if point.has_constraint() || point.is_start_of_curve_segment() {
+            return Err(EditError::EdgeNotFree);
+        }
+        let neighbour_has_same_constraint = match constraint {
+            EdgeConstraint::Horizontal => {
+                Self::neighour_edges_have_horizontal_constraint(points, edge_start_index)
+            }
+            EdgeConstraint::Vertical => {
+                Self::neighour_edges_have_vertical_constraint(points, edge_start_index)
+            }
+            EdgeConstraint::ConstWidth(_) | EdgeConstraint::ConstLength(_) => false,
+        };
+        if neighbour_has_same_constraint {
+            return Err(EditError::NeighbourHasSameConstraint(constraint));
+        }
+        points[edge_start_index].constraint = Some(constraint);
+        let same_pos = points[edge_start_index].pos;
+        Self::update_position(points, closed, edge_start_index, same_pos);
+        Ok(())
+    }
+
+    /// Applies continuity to the point and moves control points of adjacent curves to keep it
+    pub fn apply_continuity(
+        points: &mut [Point],
+        closed: bool,
+        point_index: usize,
+        continuity: ContinuityType,
+    ) -> Result<(), EditError> {
+        let has_continuity = !Self::is_endpoint(points, closed, point_index)
+            && Self::is_part_of_curve_segment(points, closed, point_index);
+        let is_second_order = matches!(continuity, ContinuityType::C2 | ContinuityType::G2);
+        if !has_continuity || (is_second_order && !Self::joins_cubic_segments(points, point_index))
+        {
+            return Err(EditError::ContinuityNotApplicable);
+        }
+        points[point_index].continuity_type = continuity;
+        let same_pos = points[point_index].pos;
+        Self::update_position(points, closed, point_index, same_pos);
+        Ok(())
+    }
+
+    pub fn update_position(
+        points: &mut [Point],
+        closed: bool,
+        point_index: usize,
+        new_position: Pos2,
+    ) {
+        points[point_index].pos = new_position;
+        let direction = if points[point_index].is_start_of_curve_segment()
+            || points[Self::get_previous_index(points, point_index)].has_constraint()
+        {
+            UpdateDirection::Left
+        } else {
+            UpdateDirection::Right
+        };
+        let previous_index = Self::get_previous_index(points, point_index);
+        let c_previous = *points[previous_index].constraint();
+        if let Some(c) = c_previous {
+            Self::apply_constraint_diff(points, previous_index, point_index, &c);
+        }
+        Self::adjust_adjacent_bezier_segments_control_points(
+            points,
+            closed,
+            previous_index,
+            1,
+            UpdateDirection::Left,
+        );
+        let c_next = *points[point_index].constraint();
+        if let Some(c) = c_next {
+            Self::apply_constraint_diff(
+                points,
+                Self::get_next_index(points, point_index),
+                point_index,
+                &c,
+            );
+        }
+        Self::adjust_adjacent_bezier_segments_control_points(
+            points,
+            closed,
+            point_index,
+            0,
+            UpdateDirection::Right,
+        );
+
+        match direction {
+            UpdateDirection::Left => {
+                Self::adjust_adjacent_edges_after_position_update(points, closed, point_index)
+            }
+            UpdateDirection::Right => {
+                Self::adjust_adjacent_edges_after_position_update_right_first(
+                    points,
+                    closed,
+                    point_index,
+                )
+            }
+        }
+    }
+
+    pub fn update_position_after_control_point_moved(
+        points: &mut [Point],
+        closed: bool,
+        point_index: usize,
+        inner_point_index: usize,
+    ) {
+        // Single control point (of quadratic segment or arc) changes both ends of the segment
+        if points[point_index].segment.control_points_count() == 1 {
+            Self::adjust_after_control_point_moved(points, closed, point_index, 0);
+            Self::adjust_after_control_point_moved(points, closed, point_index, 1);
+        } else {
+            Self::adjust_after_control_point_moved(points, closed, point_index, inner_point_index);
+        }
+    }
+
+    fn adjust_after_control_point_moved(
+        points: &mut [Point],
+        closed: bool,
+        point_index: usize,
+        inner_point_index: usize,
+    ) {
+        let direction = match inner_point_index {
+            0 => UpdateDirection::Left,
+            1 => UpdateDirection::Right,
+            _ => panic!("Invalid inner_point_index"),
+        };
+
+        match inner_point_index {
+            0 => {
+                let previous_index = Self::get_previous_index(points, point_index);
+                let c = *points[point_index].continuity_type();
+                if c != ContinuityType::G0 {
+                    if let Some(constraint) = points[previous_index].constraint() {
+                        let inner_point = Self::edge_start_handle(points, point_index);
+                        match constraint {
+                            EdgeConstraint::Horizontal => {
+                                points[point_index].pos_mut().y = inner_point.y;
+                            }
+                            EdgeConstraint::Vertical => {
+                                points[point_index].pos_mut().x = inner_point.x;
+                            }
+                            EdgeConstraint::ConstWidth(_) | EdgeConstraint::ConstLength(_) => {}
+                        }
+                    };
+                }
+            }
+            1 => {
+                let next_index = Self::get_next_index(points, point_index);
+                let c = *points[next_index].continuity_type();
+                if c != ContinuityType::G0 {
+                    if let Some(constraint) = points[next_index].constraint() {
+                        let inner_point = Self::edge_end_handle(points, point_index);
+                        match constraint {
+                            EdgeConstraint::Horizontal => {
+                                points[next_index].pos_mut().y = inner_point.y;
+                            }
+                            EdgeConstraint::Vertical => {
+                                points[next_index].pos_mut().x = inner_point.x;
+                            }
+                            EdgeConstraint::ConstWidth(_) | EdgeConstraint::ConstLength(_) => {}
+                        }
+                    };
+                }
+            }
+            _ => panic!("Invalid inner_point_index"),
+        };
+
+        Self::adjust_adjacent_bezier_segments_control_points(
+            points,
+            closed,
+            point_index,
+            inner_point_index,
+            direction,
+        );
+        match direction {
+            UpdateDirection::Left => {
+                Self::adjust_adjacent_edges_after_position_update(points, closed, point_index)
+            }
+            UpdateDirection::Right => {
+                Self::adjust_adjacent_edges_after_position_update_right_first(
+                    points,
+                    closed,
+                    point_index,
+                )
+            }
+        };
+    }
+
+    fn adjust_adjacent_bezier_segments_control_points(
+        points: &mut [Point],
+        closed: bool,
+        point_index: usize,
+        moved_control_point_id: usize,
+        update_direction: UpdateDirection,
+    ) {
+        #[cfg(feature = "show_debug_info")]
+        println!("Adjusting bezier segments control point in {point_index}");
+
+        // There is no continuity to keep in the ends of an open path
+        if Self::is_endpoint(points, closed, point_index) {
+            return;
+        }
+
+        match moved_control_point_id {
+            0 => {
+                if Self::is_end_of_curve_segment(points, closed, point_index) {
+                    Self::adjust_bezier_segment_control_points_from_end(
+                        points,
+                        point_index,
+                        update_direction,
+                    );
+                }
+
+                if points[point_index].is_start_of_curve_segment() {
+                    Self::adjust_bezier_segment_control_points_from_start(
+                        points,
+                        point_index,
+                        update_direction,
+                    );
+                }
+            }
+            1 => {
+                if points[point_index].is_start_of_curve_segment() {
+                    Self::adjust_bezier_segment_control_points_from_start(
+                        points,
+                        point_index,
+                        update_direction,
+                    );
+                }
+                if Self::is_end_of_curve_segment(points, closed, point_index) {
+                    Self::adjust_bezier_segment_control_points_from_end(
+                        points,
+                        point_index,
+                        update_direction,
+                    );
+                }
+            }
+            _ => eprintln!("Moved control point id should never be greater than 1"),
+        }
+    }
+
+    fn adjust_bezier_segment_control_points_from_start(
+        points: &mut [Point],
+        point_index: usize,
+        update_direction: UpdateDirection,
+    ) {
+        match points[point_index].continuity_type() {
+            ContinuityType::G0 => {}
+            ContinuityType::C1 => {
+                Self::adjust_c1_continuity(points, point_index, update_direction);
+            }
+            ContinuityType::G1 => {
+                Self::adjust_g1_coninuity(points, point_index, update_direction);
+            }
+            ContinuityType::C2 => {
+                Self::adjust_c1_continuity(points, point_index, update_direction);
+                Self::adjust_c2_continuity(points, point_index, update_direction);
+            }
+            ContinuityType::G2 => {
+                Self::adjust_g1_coninuity(points, point_index, update_direction);
+                Self::adjust_g2_continuity(points, point_index, update_direction);
+            }
+        }
+    }
+
+    fn adjust_bezier_segment_control_points_from_end(
+        points: &mut [Point],
+        point_index: usize,
+        update_direction: UpdateDirection,
+    ) {
+        match points[point_index].continuity_type() {
+            ContinuityType::G0 => {}
+            ContinuityType::C1 => {
+                Self::adjust_c1_continuity(points, point_index, update_direction);
+            }
+            ContinuityType::G1 => {
+                Self::adjust_g1_coninuity(points, point_index, update_direction);
+            }
+            ContinuityType::C2 => {
+                Self::adjust_c1_continuity(points, point_index, update_direction);
+                Self::adjust_c2_continuity(points, point_index, update_direction);
+            }
+            ContinuityType::G2 => {
+                Self::adjust_g1_coninuity(points, point_index, update_direction);
+                Self::adjust_g2_continuity(points, point_index, update_direction);
+            }
+        }
+    }
+
+    fn new_position_for_adjusting_g1_continuity(
+        coninuity_point: Pos2,
+        end_to_stay: Pos2,
+        end_to_update: Pos2,
+    ) -> Pos2 {
+        let unchanged_vector = end_to_stay - coninuity_point;
+        let vector_length = end_to_update.distance(coninuity_point);
+        coninuity_point - unchanged_vector.normalized() * vector_length
+    }
+
+    fn adjust_g1_coninuity(
+        points: &mut [Point],
+        point_index: usize,
+        update_direction: UpdateDirection,
+    ) {
+        #[cfg(feature = "show_debug_info")]
+        println!(
+            "Adjusting G1 coninuity in {} (direction: {:?})",
+            point_index, update_direction
+        );
+
+        let previous_point = Self::get_previous_index(points, point_index);
+
+        let continuity_point = *points[point_index].pos();
+
+        match update_direction {
+            UpdateDirection::Left => {
+                let c = points[point_index]
+                    .constraint()
+                    .unwrap_or(EdgeConstraint::ConstWidth(0));
+
+                Self::apply_constraint_to_start_handle(points, point_index, &c);
+
+                let end_to_stay = Self::edge_start_handle(points, point_index);
+                let end_to_update = Self::edge_end_handle(points, previous_point);
+
+                let new_position = Self::new_position_for_adjusting_g1_continuity(
+                    continuity_point,
+                    end_to_stay,
+                    end_to_update,
+                );
+
+                Self::set_edge_end_handle(points, previous_point, new_position);
+            }
+            UpdateDirection::Right => {
+                let c = points[point_index]
+                    .constraint()
+                    .unwrap_or(EdgeConstraint::ConstWidth(0));
+
+                Self::apply_constraint_to_end_handle(points, previous_point, point_index, &c);
+
+                let end_to_stay = Self::edge_end_handle(points, previous_point);
+                let end_to_update = Self::edge_start_handle(points, point_index);
+
+                let new_position = Self::new_position_for_adjusting_g1_continuity(
+                    continuity_point,
+                    end_to_stay,
+                    end_to_update,
+                );
+
+                Self::set_edge_start_handle(points, point_index, new_position);
+            }
+        }
+    }
+
+    fn new_position_for_adjusting_c1_continuity(
+        coninuity_point: Pos2,
+        end_to_stay: Pos2,
+        end_to_preserve_length: Pos2,
+        scale: f32,
+    ) -> Pos2 {
+        let unchanged_vector = end_to_stay - coninuity_point;
+        let vector_length = end_to_preserve_length.distance(coninuity_point) * scale;
+        coninuity_point - unchanged_vector.normalized() * vector_length
+    }
+
+    fn adjust_c1_continuity(
+        points: &mut [Point],
+        point_index: usize,
+        update_direction: UpdateDirection,
+    ) {
+        #[cfg(feature = "show_debug_info")]
+        println!(
+            "Adjusting C1 coninuity in {} (direction: {:?})",
+            point_index, update_direction
+        );
+
+        let previous_point = Self::get_previous_index(points, point_index);
+
+        let continuity_point = *points[point_index].pos();
+
+        match update_direction {
+            UpdateDirection::Left => {
+                let c = points[point_index]
+                    .constraint()
+                    .unwrap_or(EdgeConstraint::ConstWidth(0));
+
+                Self::apply_constraint_to_start_handle(points, point_index, &c);
+
+                let end_to_stay = Self::edge_start_handle(points, point_index);
+
+                // Derivatives on both sides have to be equal, so handles lengths are scaled
+                // by ratio of derivative factors of both segments
+                let scale = points[point_index].segment.derivative_factor()
+                    / points[previous_point].segment.derivative_factor();
+
+                let new_position = Self::new_position_for_adjusting_c1_continuity(
+                    continuity_point,
+                    end_to_stay,
+                    end_to_stay,
+                    scale,
+                );
+
+                Self::set_edge_end_handle(points, previous_point, new_position);
+            }
+            UpdateDirection::Right => {
+                let c = points[point_index]
+                    .constraint()
+                    .unwrap_or(EdgeConstraint::ConstWidth(0));
+
+                match c {
+                    EdgeConstraint::ConstWidth(w) => {
+                        if w > 0 && points[previous_point].is_start_of_curve_segment() {
+                            let new_position = Self::calculate_position_for_keeping_width(
+                                w as f32 / points[previous_point].segment.derivative_factor(),
+                                continuity_point,
+                                Self::edge_end_handle(points, previous_point),
+                            );
+                            Self::set_edge_end_handle(points, previous_point, new_position);
+                        }
+                    }
+                    _ => Self::apply_constraint_to_end_handle(
+                        points,
+                        previous_point,
+                        point_index,
+                        &c,
+                    ),
+                }
+
+                let end_to_stay = Self::edge_end_handle(points, previous_point);
+
+                let scale = points[previous_point].segment.derivative_factor()
+                    / points[point_index].segment.derivative_factor();
+
+                let new_position = Self::new_position_for_adjusting_c1_continuity(
+                    continuity_point,
+                    end_to_stay,
+                    end_to_stay,
+                    scale,
+                );
+
+                Self::set_edge_start_handle(points, point_index, new_position);
+            }
+        };
+    }
+
+    /// Returns control polygons of the cubic segments that end and start in the point,
+    /// or None when any of them is not a cubic bezier segment
+    fn adjacent_cubic_control_polygons(
+        points: &[Point],
+        point_index: usize,
+    ) -> Option<([Pos2; 4], [Pos2; 4])> {
+        let previous_index = Self::get_previous_index(points, point_index);
+        let next_index = Self::get_next_index(points, point_index);
+        match (
+            &points[previous_index].segment,
+            &points[point_index].segment,
+        ) {
+            (EdgeSegment::CubicBezier(previous), EdgeSegment::CubicBezier(next)) => Some((
+                previous.control_polygon(&points[previous_index], &points[point_index]),
+                next.control_polygon(&points[point_index], &points[next_index]),
+            )),
+            _ => None,
+        }
+    }
+
+    /// Moves second control point of the updated segment, so that second derivatives
+    /// of both segments are equal in the point. C1 continuity has to be already kept.
+    fn adjust_c2_continuity(
+        points: &mut [Point],
+        point_index: usize,
+        update_direction: UpdateDirection,
+    ) {
+        #[cfg(feature = "show_debug_info")]
+        println!(
+            "Adjusting C2 coninuity in {} (direction: {:?})",
+            point_index, update_direction
+        );
+
+        let Some((previous, next)) = Self::adjacent_cubic_control_polygons(points, point_index)
+        else {
+            return;
+        };
+        let previous_index = Self::get_previous_index(points, point_index);
+
+        // p3 - 2 p2 + p1 = q0 - 2 q1 + q2, where p3 = q0
+        match update_direction {
+            UpdateDirection::Left => {
+                let new_position = next[2] + (previous[2] - next[1]) * 2.0;
+                if let EdgeSegment::CubicBezier(data) = &mut points[previous_index].segment {
+                    data.update_inner_point_position(0, new_position);
+                }
+            }
+            UpdateDirection::Right => {
+                let new_position = previous[1] + (next[1] - previous[2]) * 2.0;
+                if let EdgeSegment::CubicBezier(data) = &mut points[point_index].segment {
+                    data.update_inner_point_position(1, new_position);
+                }
+            }
+        }
+    }
+
+    /// Moves second control point of the updated segment perpendicularly to the tangent,
+    /// so that curvatures of both segments are equal in the point. G1 continuity has to be already kept.
+    fn adjust_g2_continuity(
+        points: &mut [Point],
+        point_index: usize,
+        update_direction: UpdateDirection,
+    ) {
+        #[cfg(feature = "show_debug_info")]
+        println!(
+            "Adjusting G2 coninuity in {} (direction: {:?})",
+            point_index, update_direction
+        );
+
+        let Some((previous, next)) = Self::adjacent_cubic_control_polygons(points, point_index)
+        else {
+            return;
+        };
+        let previous_index = Self::get_previous_index(points, point_index);
+        let continuity_point = *points[point_index].pos();
+
+        let tangent = (next[1] - continuity_point).normalized();
+        if tangent == Vec2::ZERO || !tangent.is_finite() {
+            return;
+        }
+        // Signed distances below are measured along this normal
+        let normal = Vec2::new(-tangent.y, tangent.x);
+        // Curvature of a cubic in its end equals 2/3 * h / a^2, where a is length of the handle
+        // and h is signed distance of the second control point from the tangent line
+        let previous_handle = continuity_point.distance(previous[2]);
+        let next_handle = continuity_point.distance(next[1]);
+        let previous_distance = Geometry::cross(tangent, previous[1] - previous[2]);
+        let next_distance = Geometry::cross(tangent, next[2] - next[1]);
+        if previous_handle <= f32::EPSILON || next_handle <= f32::EPSILON {
+            return;
+        }
+
+        match update_direction {
+            UpdateDirection::Left => {
+                let curvature = next_distance / (next_handle * next_handle);
+                let target = curvature * previous_handle * previous_handle;
+                let new_position = previous[1] + normal * (target - previous_distance);
+                if let EdgeSegment::CubicBezier(data) = &mut points[previous_index].segment {
+                    data.update_inner_point_position(0, new_position);
+                }
+            }
+            UpdateDirection::Right => {
+                let curvature = previous_distance / (previous_handle * previous_handle);
+                let target = curvature * next_handle * next_handle;
+                let new_position = next[2] + normal * (target - next_distance);
+                if let EdgeSegment::CubicBezier(data) = &mut points[point_index].segment {
+                    data.update_inner_point_position(1, new_position);
+                }
+            }
+        }
+    }
+
+    /// Returns handle of the edge, that lies next to its start
+    fn edge_start_handle(points: &[Point], edge_start_index: usize) -> Pos2 {
+        let edge_end_index = Self::get_next_index(points, edge_start_index);
+        points[edge_start_index]
+            .segment
+            .start_handle(&points[edge_start_index], &points[edge_end_index])
+    }
+
+    /// Returns handle of the edge, that lies next to its end
+    fn edge_end_handle(points: &[Point], edge_start_index: usize) -> Pos2 {
+        let edge_end_index = Self::get_next_index(points, edge_start_index);
+        points[edge_start_index]
+            .segment
+            .end_handle(&points[edge_start_index], &points[edge_end_index])
+    }
+
+    /// Moves handle next to the start of the edge, for straight edges their end is moved instead
+    fn set_edge_start_handle(points: &mut [Point], edge_start_index: usize, new_position: Pos2) {
+        let edge_end_index = Self::get_next_index(points, edge_start_index);
+        if points[edge_start_index].is_start_of_curve_segment() {
+            let (start, end) = (points[edge_start_index], points[edge_end_index]);
+            points[edge_start_index]
+                .segment
+                .set_start_handle(&start, &end, new_position);
+        } else {
+            *points[edge_end_index].pos_mut() = new_position;
+        }
+    }
+
+    /// Moves handle next to the end of the edge, for straight edges their start is moved instead
+    fn set_edge_end_handle(points: &mut [Point], edge_start_index: usize, new_position: Pos2) {
+        let edge_end_index = Self::get_next_index(points, edge_start_index);
+        if points[edge_start_index].is_start_of_curve_segment() {
+            let (start, end) = (points[edge_start_index], points[edge_end_index]);
+            points[edge_start_index]
+                .segment
+                .set_end_handle(&start, &end, new_position);
+        } else {
+            *points[edge_start_index].pos_mut() = new_position;
+        }
+    }
+
+    /// Aligns start handle of the edge with its start, according to horizontal or vertical constraint
+    fn apply_constraint_to_start_handle(
+        points: &mut [Point],
+        edge_start_index: usize,
+        constraint: &EdgeConstraint,
+    ) {
+        let continuity_point = *points[edge_start_index].pos();
+        let mut handle = Self::edge_start_handle(points, edge_start_index);
+        match constraint {
+            EdgeConstraint::Horizontal => handle.y = continuity_point.y,
+            EdgeConstraint::Vertical => handle.x = continuity_point.x,
+            EdgeConstraint::ConstWidth(_) | EdgeConstraint::ConstLength(_) => return,
+        }
+        Self::set_edge_start_handle(points, edge_start_index, handle);
+    }
+
+    /// Aligns end handle of the edge with the continuity point, according to horizontal or vertical constraint
+    fn apply_constraint_to_end_handle(
+        points: &mut [Point],
+        edge_start_index: usize,
+        continuity_point_index: usize,
+        constraint: &EdgeConstraint,
+    ) {
+        let continuity_point = *points[continuity_point_index].pos();
+        let mut handle = Self::edge_end_handle(points, edge_start_index);
+        match constraint {
+            EdgeConstraint::Horizontal => handle.y = continuity_point.y,
+            EdgeConstraint::Vertical => handle.x = continuity_point.x,
+            EdgeConstraint::ConstWidth(_) | EdgeConstraint::ConstLength(_) => return,
+        }
+        Self::set_edge_end_handle(points, edge_start_index, handle);
+    }
+
+    fn adjust_adjacent_edges_after_position_update(
+        points: &mut [Point],
+        closed: bool,
+        point_index: usize,
+    ) {
+        #[cfg(feature = "show_debug_info")]
+        {
+            println!("================================================");
+            println!(
+                "Starting adjustment process (left first) from: {}",
+                point_index
+            );
+        }
+
+        Self::adjust_adjacent_edges_after_position_update_only_left(points, closed, point_index);
+        Self::adjust_adjacent_edges_after_position_update_only_right(points, closed, point_index);
+
+        #[cfg(feature = "show_debug_info")]
+        println!("================================================");
+    }
+
+    fn adjust_adjacent_edges_after_position_update_right_first(
+        points: &mut [Point],
+        closed: bool,
+        point_index: usize,
+    ) {
+        #[cfg(feature = "show_debug_info")]
+        {
+            println!("================================================");
+            println!(
+                "Starting adjustment process (right first) from: {}",
+                point_index
+            );
+        }
+
+        Self::adjust_adjacent_edges_after_position_update_only_right(points, closed, point_index);
+        Self::adjust_adjacent_edges_after_position_update_only_left(points, closed, point_index);
+
+        #[cfg(feature = "show_debug_info")]
+        println!("================================================");
+    }
+
+    fn adjust_adjacent_edges_after_position_update_only_left(
+        points: &mut [Point],
+        closed: bool,
+        point_index: usize,
+    ) {
+        let mut left = point_index;
+        Self::adjust_adjacent_bezier_segments_control_points(
+            points,
+            closed,
+            point_index,
+            0,
+            UpdateDirection::Left,
+        );
+
+        // Open path is walked only to its first point
+        while Self::get_previous_index(points, left) != point_index && (closed || left != 0) {
+            let previous = Self::get_previous_index(points, left);
+            Self::adjust_moved_point_edge_end(
+                points,
+                closed,
+                left,
+                Self::get_previous_index(points, previous) != point_index || points.len() == 3,
+            );
+            left = previous;
+        }
+    }
+
+    fn adjust_adjacent_edges_after_position_update_only_right(
+        points: &mut [Point],
+        closed: bool,
+        point_index: usize,
+    ) {
+        let mut right = point_index;
+        Self::adjust_adjacent_bezier_segments_control_points(
+            points,
+            closed,
+            point_index,
+            1,
+            UpdateDirection::Right,
+        );
+
+        // Open path is walked only to its last point
+        while Self::get_next_index(points, right) != point_index
+            && (closed || right + 1 != points.len())
+        {
+            let next = Self::get_next_index(points, right);
+            Self::adjust_moved_point_edge_start(
+                points,
+                closed,
+                right,
+                Self::get_next_index(points, next) != point_index || points.len() == 3,
+            );
+            right = next;
+        }
+    }
+
+    fn adjust_moved_point_edge_start(
+        points: &mut [Point],
+        closed: bool,
+        edge_start_index: usize,
+        adjust_bezier: bool,
+    ) {
+        #[cfg(feature = "show_debug_info")]
+        println!("Adjusting point {edge_start_index} from start");
+
+        let constraint = *points[edge_start_index].constraint();
+        let edge_end_index = Self::get_next_index(points, edge_start_index);
+        if let Some(constraint) = constraint {
+            #[cfg(feature = "show_debug_info")]
+            println!(
+                "Applying constriaint (from start point): {}-{}",
+                edge_start_index, edge_end_index
+            );
+            Self::apply_constraint_diff(points, edge_end_index, edge_start_index, &constraint);
+        }
+        if adjust_bezier {
+            Self::adjust_adjacent_bezier_segments_control_points(
+                points,
+                closed,
+                edge_end_index,
+                0,
+                UpdateDirection::Right,
+            );
+        }
+    }
+
+    fn adjust_moved_point_edge_end(
+        points: &mut [Point],
+        closed: bool,
+        edge_end_index: usize,
+        adjust_bezier: bool,
+    ) {
+        #[cfg(feature = "show_debug_info")]
+        println!("Adjusting point {edge_end_index} from end");
+
+        let edge_start_index = Self::get_previous_index(points, edge_end_index);
+        let constraint = *points[edge_start_index].constraint();
+        if let Some(constraint) = constraint {
+            #[cfg(feature = "show_debug_info")]
+            println!(
+                "Applying constraint on edge (from end point): {}-{}",
+                edge_start_index, edge_end_index
+            );
+            Self::apply_constraint_diff(points, edge_start_index, edge_end_index, &constraint);
+        }
+        if adjust_bezier {
+            Self::adjust_adjacent_bezier_segments_control_points(
+                points,
+                closed,
+                edge_start_index,
+                0,
+                UpdateDirection::Left,
+            );
+        }
+    }
+
+    fn apply_constraint_diff(
+        points: &mut [Point],
+        point_index: usize,
+        other_edge_end_index: usize,
+        constraint: &EdgeConstraint,
+    ) {
+        match constraint {
+            EdgeConstraint::Horizontal => {
+                points[point_index].pos_mut().y = points[other_edge_end_index].pos().y;
+            }
+            EdgeConstraint::Vertical => {
+                points[point_index].pos_mut().x = points[other_edge_end_index].pos().x;
+            }
+            EdgeConstraint::ConstWidth(width) => {
+                let new_position = Self::calculate_position_for_keeping_width(
+                    *width as f32,
+                    *points[other_edge_end_index].pos(),
+                    *points[point_index].pos(),
+                );
+                *points[point_index].pos_mut() = new_position;
+            }
+            EdgeConstraint::ConstLength(length) => {
+                Self::keep_curve_length(points, point_index, other_edge_end_index, *length as f32);
+            }
+        }
+    }
+
+    /// Moves the updated end of the curve along the line going through both its ends, so that length
+    /// of the curve equals `length`, without moving control points. If there is no such position,
+    /// the whole curve is scaled around the other end instead.
+    fn keep_curve_length(
+        points: &mut [Point],
+        point_index: usize,
+        other_edge_end_index: usize,
+        length: f32,
+    ) {
+        let pos_to_update = *points[point_index].pos();
+        let pos_to_stay = *points[other_edge_end_index].pos();
+        let edge_start_index = if Self::get_next_index(points, point_index) == other_edge_end_index
+        {
+            point_index
+        } else {
+            other_edge_end_index
+        };
+        if !points[edge_start_index].is_start_of_curve_segment() {
+            points[point_index].pos =
+                Self::calculate_position_for_keeping_width(length, pos_to_stay, pos_to_update);
+            return;
+        }
+        let direction = (pos_to_update - pos_to_stay).normalized();
+        if !direction.is_finite() {
+            return;
+        }
+
+        // Difference between length of the curve with updated end at given distance and the required one
+        let length_error = |points: &[Point], distance: f32| {
+            let mut moved = points[point_index];
+            moved.pos = pos_to_stay + direction * distance;
+            let (start, end) = if edge_start_index == point_index {
+                (moved, points[other_edge_end_index])
+            } else {
+                (points[other_edge_end_index], moved)
+            };
+            points[edge_start_index].segment.length(&start, &end) - length
+        };
+
+        let current_distance = pos_to_update.distance(pos_to_stay);
+        match Self::find_distance_for_keeping_length(
+            |distance| length_error(points, distance),
+            current_distance,
+        ) {
+            Some(distance) => points[point_index].pos = pos_to_stay + direction * distance,
+            None => {
+                let current_length = length_error(points, current_distance) + length;
+                if current_length <= f32::EPSILON {
+                    return;
+                }
+                let factor = length / current_length;
+                points[point_index].pos = pos_to_stay + (pos_to_update - pos_to_stay) * factor;
+                points[edge_start_index].segment.scale(pos_to_stay, factor);
+            }
+        }
+    }
+
+    /// Finds distance for which `length_error` equals zero, starting from the current distance.
+    /// First range containing it is found, which is then bisected.
+    fn find_distance_for_keeping_length(
+        length_error: impl Fn(f32) -> f32,
+        current_distance: f32,
+    ) -> Option<f32> {
+        let current_error = length_error(current_distance);
+        let (mut low, mut high) = if current_error < 0.0 {
+            let mut step = -current_error;
+            let mut high = current_distance + step;
+            let mut iterations = 0;
+            while length_error(high) < 0.0 {
+                iterations += 1;
+                if iterations > Self::MAX_LENGTH_SEARCH_ITERATIONS {
+                    return None;
+                }
+                step *= 2.0;
+                high += step;
+            }
+            (current_distance, high)
+        } else {
+            if length_error(0.0) > 0.0 {
+                return None;
+            }
+            (0.0, current_distance)
+        };
+        for _ in 0..Self::MAX_LENGTH_SEARCH_ITERATIONS {
+            let middle = (low + high) / 2.0;
+            if length_error(middle) < 0.0 {
+                low = middle;
+            } else {
+                high = middle;
+            }
+        }
+        Some((low + high) / 2.0)
+    }
+
+    fn calculate_position_for_keeping_width(
+        width: f32,
+        pos_to_stay: Pos2,
+        pos_to_update: Pos2,
+    ) -> Pos2 {
+        let angle_between =
+            (pos_to_update.y - pos_to_stay.y).atan2(pos_to_update.x - pos_to_stay.x);
+        Pos2 {
+            x: pos_to_stay.x + width * angle_between.cos(),
+            y: pos_to_stay.y + width * angle_between.sin(),
+        }
+    }
+
+    pub fn get_middle_point(start: &Point, end: &Point) -> Pos2 {
+        (start.pos + end.pos().to_vec2()) / 2.0
+    }
+
+    pub fn get_next_index(points: &[Point], point_index: usize) -> usize {
+        (point_index + 1) % points.len()
+    }
+
+    pub fn get_previous_index(points: &[Point], point_index: usize) -> usize {
+        if point_index == 0 {
+            points.len() - 1
+        } else {
+            point_index - 1
+        }
+    }
+
+    pub fn add_on_edge(points: &mut Vec<Point>, edge_start_index: usize) {
+        // If the edge we are dividing had any constraint we remove it,
+        // as there should be two new edges each without any constraint
+        // Same goes for bezier segment
+        points[edge_start_index].remove_constraint();
+        points[edge_start_index].remove_curve();
+        let next_index = Self::get_next_index(points, edge_start_index);
+
+        // Adding new edge is just inserting a point at correct index
+        let new_point = Self::get_middle_point(&points[edge_start_index], &points[next_index]);
+        points.insert(next_index, Point::new(new_point));
+    }
+
+    pub fn remove_at(points: &mut Vec<Point>, closed: bool, point_index: usize) {
+        // If the point behind it has any restriction, we remove it
+        // Restrisction on the removed point is removed with it, so we dont care about it
+        // Same goes for bezier data
+        let previous_index = Self::get_previous_index(points, point_index);
+        points[previous_index].remove_constraint();
+        points[previous_index].remove_curve();
+        points.remove(point_index);
+        let point_index = point_index % points.len();
+        let pos = *points[point_index].pos();
+        Self::update_position(points, closed, point_index, pos);
+    }
+
+    /// Changes closed polygon into an open path, which starts and ends in given point
+    pub fn open_at(points: &mut Vec<Point>, point_index: usize) {
+        points.rotate_left(point_index);
+        // Last point of an open path starts no edge
+        let end = Point::new_all(
+            *points[0].pos(),
+            None,
+            EdgeSegment::Line,
+            ContinuityType::G0,
+        );
+        points.push(end);
+    }
+
+    /// Returns points from `start_index` to `end_index` (going forward, around the end of a closed
+    /// polygon when needed) as an open path. Edges between them keep their constraints and curves.
+    pub fn copy_run(points: &[Point], start_index: usize, end_index: usize) -> Vec<Point> {
+        let mut run = vec![points[start_index]];
+        let mut id = start_index;
+        while id != end_index {
+            id = Self::get_next_index(points, id);
+            run.push(points[id]);
+        }
+        // Last point of an open path starts no edge
+        if let Some(last) = run.last_mut() {
+            last.remove_constraint();
+            last.remove_curve();
+        }
+        run
+    }
+
+    /// Connects the last point of an open path with the first one. When they are in the same place,
+    /// they are merged into one point, otherwise they are connected with a line.
+    pub fn close(points: &mut Vec<Point>) {
+        let last_index = points.len() - 1;
+        if points.len() > 3
+            && points[last_index].pos().distance(*points[0].pos()) < Geometry::SAME_POINT_TOLERANCE
+        {
+            points.remove(last_index);
+        }
+        // Both ends became ordinary points, so continuity has to be kept in them now
+        let pos = *points[0].pos();
+        Self::update_position(points, true, 0, pos);
+    }
+
+    pub fn update_position_all(points: &mut [Point], diff: Vec2) {
+        for point in points {
+            *point.pos_mut() += diff;
+            point.segment.translate(diff);
+        }
+    }
+
+    /// Returns true if the edge that starts in edge_start_index
+    /// contains the given point
+    pub fn contains_point(points: &[Point], edge_start_index: usize, point: &Pos2) -> bool {
+        const TOLERANCE: f32 = 20.0;
+        const TOLERANCE_SAME_DIM: f32 = 5.0;
+        let start = points[edge_start_index].pos();
+        let end = points[Self::get_next_index(points, edge_start_index)].pos();
+
+        if points[edge_start_index].is_start_of_curve_segment() {
+            return points[edge_start_index].segment.contains_point(
+                &points[edge_start_index],
+                &points[Self::get_next_index(points, edge_start_index)],
+                *point,
+                TOLERANCE_SAME_DIM,
+            );
+        }
+
+        let min_x = start.x.min(end.x);
+        let max_x = start.x.max(end.x);
+        let min_y = start.y.min(end.y);
+        let max_y = start.y.max(end.y);
+
+        if (start.x - end.x).abs() <= TOLERANCE_SAME_DIM
+            && (point.x - start.x).abs() <= TOLERANCE_SAME_DIM
+            && point.y >= min_y
+            && point.y <= max_y
+        {
+            return true;
+        }
+
+        if (start.y - end.y).abs() <= TOLERANCE_SAME_DIM
+            && (point.y - start.y).abs() <= TOLERANCE_SAME_DIM
+            && point.x >= min_x
+            && point.x <= max_x
+        {
+            return true;
+        }
+
+        let dx_p = point.x - start.x;
+        let dy_p = point.y - start.y;
+
+        let dx_e = end.x - start.x;
+        let dy_e = end.y - start.y;
+
+        let squared_edge_length = dx_e * dx_e + dy_e * dy_e;
+
+        let cross = dx_p * dy_e - dy_p * dx_e;
+
+        if cross * cross / squared_edge_length > TOLERANCE {
+            return false;
+        }
+
+        point.x >= min_x && point.x <= max_x && point.y >= min_y && point.y <= max_y
+    }
+
+    pub fn neighour_edges_have_vertical_constraint(points: &[Point], point_index: usize) -> bool {
+        let previous_edge_start = Self::get_previous_index(points, point_index);
+        let next_edge_start = Self::get_next_index(points, point_index);
+        points[previous_edge_start].has_vertical_constraint()
+            || points[next_edge_start].has_vertical_constraint()
+    }
+
+    pub fn neighour_edges_have_horizontal_constraint(points: &[Point], point_index: usize) -> bool {
+        let previous_edge_start = Self::get_previous_index(points, point_index);
+        let next_edge_start = Self::get_next_index(points, point_index);
+        points[previous_edge_start].has_horizontal_constraint()
+            || points[next_edge_start].has_horizontal_constraint()
+    }
+
+    /// Returns ids of edges whose constraints are broken by more than `tolerance`
+    pub fn unsatisfied_constraints(points: &[Point], closed: bool, tolerance: f32) -> Vec<usize> {
+        (0..Self::edges_count(points, closed))
+            .filter(|&id| {
+                let (start, end) = (&points[id], &points[Self::get_next_index(points, id)]);
+                let diff = *end.pos() - *start.pos();
+                match start.constraint {
+                    None => false,
+                    Some(EdgeConstraint::Horizontal) => diff.y.abs() > tolerance,
+                    Some(EdgeConstraint::Vertical) => diff.x.abs() > tolerance,
+                    Some(EdgeConstraint::ConstWidth(width)) => {
+                        (diff.length() - width as f32).abs() > tolerance
+                    }
+                    Some(EdgeConstraint::ConstLength(length)) => {
+                        (start.segment.length(start, end) - length as f32).abs() > tolerance
+                    }
+                }
+            })
+            .collect()
+    }
+
+    /// Returns sum of lengths of all edges, measured along curves.
+    /// For an open path it's the length of the path.
+    pub fn perimeter(points: &[Point], closed: bool) -> f32 {
+        (0..Self::edges_count(points, closed))
+            .map(|id| {
+                let id_next = Self::get_next_index(points, id);
+                points[id].segment.length(&points[id], &points[id_next])
+            })
+            .sum()
+    }
+
+    /// Returns vertices of the polygon with bezier segments replaced by polylines
+    /// that are not further than `tolerance` from the curves
+    pub fn flatten(points: &[Point], tolerance: f32) -> Vec<Pos2> {
+        let mut flattened = Vec::with_capacity(points.len());
+        for id in 0..points.len() {
+            flattened.push(*points[id].pos());
+            let id_next = Self::get_next_index(points, id);
+            flattened.extend(
+                points[id]
+                    .segment
+                    .flatten(&points[id], &points[id_next], tolerance),
+            );
+        }
+        flattened
+    }
+
+    pub fn get_points_between_for_initial_bezier(start: &Point, end: &Point) -> [Pos2; 2] {
+        const OFFSET: f32 = Point::INITIAL_CURVE_OFFSET;
+        let diff = *end.pos() - *start.pos();
+        let p = Vec2::new(-diff.y, diff.x).normalized() * OFFSET;
+        let a = *start.pos() + diff * 1.0 / 3.0 + p;
+        let b = *start.pos() + diff * 2.0 / 3.0 + p;
+        [a, b]
+    }
+
+    /// Returns sweep of an arc bulging to the same side as the initial bezier segment
+    pub fn get_sweep_for_initial_arc(start: &Point, end: &Point) -> f32 {
+        let length = start.pos().distance(*end.pos());
+        -4.0 * (2.0 * Self::INITIAL_CURVE_OFFSET / length.max(f32::EPSILON)).atan()
+    }
+}
+
+/// Polygons shared by unit tests of the model
+#[cfg(test)]
+pub(crate) mod fixtures {
+    use emath::pos2;
+
+    use super::Point;
+    use crate::{geometry::Geometry, shape::Shape};
+
+    /// Points in given positions, joined with straight edges without constraints
+    pub(crate) fn points(positions: &[(f32, f32)]) -> Vec<Point> {
+        positions
+            .iter()
+            .map(|&(x, y)| Point::new(pos2(x, y)))
+            .collect()
+    }
+
+    /// Square with the first corner in (x, y), going counterclockwise (in coordinates where y grows upwards)
+    pub(crate) fn square(x: f32, y: f32, size: f32) -> Vec<Point> {
+        points(&[(x, y), (x + size, y), (x + size, y + size), (x, y + size)])
+    }
+
+    /// Area covered by the shapes, holes are subtracted
+    pub(crate) fn area(shapes: &[Shape]) -> f32 {
+        shapes
+            .iter()
+            .map(|shape| {
+                let rings = shape.flattened_rings(0.1);
+                let outline = Geometry::signed_area(&rings[0]).abs();
+                let holes: f32 = rings[1..]
+                    .iter()
+                    .map(|ring| Geometry::signed_area(ring).abs())
+                    .sum();
+                outline - holes
+            })
+            .sum()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use emath::pos2;
+
+    use super::*;
+
+    fn triangle() -> Vec<Point> {
+        fixtures::points(&[(0.0, 0.0), (100.0, 30.0), (50.0, 80.0)])
+    }
+
+    #[test]
+    fn constrain_edge_moves_its_end() {
+        let mut points = triangle();
+        Point::constrain_edge(&mut points, true, 0, EdgeConstraint::Horizontal).unwrap();
+        assert_eq!(points[0].pos().y, points[1].pos().y);
+        assert!(Point::unsatisfied_constraints(&points, true, 0.01).is_empty());
+    }
+
+    #[test]
+    fn constrain_edge_rejects_taken_edges() {
+        let mut points = triangle();
+        Point::constrain_edge(&mut points, true, 0, EdgeConstraint::Horizontal).unwrap();
+        assert!(matches!(
+            Point::constrain_edge(&mut points, true, 0, EdgeConstraint::Vertical),
+            Err(EditError::EdgeNotFree)
+        ));
+        assert!(matches!(
+            Point::constrain_edge(&mut points, true, 1, EdgeConstraint::Horizontal),
+            Err(EditError::NeighbourHasSameConstraint(
+                EdgeConstraint::Horizontal
+            ))
+        ));
+    }
+
+    #[test]
+    fn update_position_keeps_constraints() {
+        let mut points = triangle();
+        Point::constrain_edge(&mut points, true, 0, EdgeConstraint::Horizontal).unwrap();
+        Point::constrain_edge(&mut points, true, 1, EdgeConstraint::ConstWidth(60)).unwrap();
+        Point::update_position(&mut points, true, 1, pos2(120.0, 50.0));
+        assert_eq!(*points[1].pos(), pos2(120.0, 50.0));
+        assert!(Point::unsatisfied_constraints(&points, true, 0.01).is_empty());
+    }
+
+    #[test]
+    fn apply_continuity_needs_a_curve() {
+        let mut points = triangle();
+        assert!(matches!(
+            Point::apply_continuity(&mut points, true, 1, ContinuityType::G1),
+            Err(EditError::ContinuityNotApplicable)
+        ));
+        points[0].init_bezier_data([pos2(30.0, -20.0), pos2(70.0, 0.0)]);
+        Point::apply_continuity(&mut points, true, 1, ContinuityType::G1).unwrap();
+        // Incoming tangent points along the outgoing straight edge
+        let EdgeSegment::CubicBezier(bezier_data) = points[0].segment() else {
+            panic!("Curve was removed");
+        };
+        let incoming = *points[1].pos() - bezier_data.inner_points()[1];
+        let outgoing = *points[2].pos() - *points[1].pos();
+        assert!(Geometry::cross(incoming, outgoing).abs() < 0.01 * incoming.length());
+        assert!(incoming.dot(outgoing) > 0.0);
+    }
+}
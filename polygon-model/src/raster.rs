@@ -0,0 +1,52 @@
+use emath::Pos2;
+
+/// Rasterization of lines, shared by the editor canvas and rendering to images
+pub struct Raster;
+
+impl Raster {
+    /// Calls `plot` for every pixel of the line from `start` to `end` chosen by the Bresenham
+    /// algorithm, both ends included
+    pub fn line(start: Pos2, end: Pos2, mut plot: impl FnMut(i32, i32)) {
+        let x1 = start.x as i32;
+        let y1 = start.y as i32;
+        let x2 = end.x as i32;
+        let y2 = end.y as i32;
+
+        let dx = x2 - x1;
+        let dy = y2 - y1;
+
+        let abs_dx = dx.abs();
+        let abs_dy = dy.abs();
+
+        let mut x = x1;
+        let mut y = y1;
+
+        plot(x, y);
+
+        if abs_dx > abs_dy {
+            let mut d = 2 * abs_dy - abs_dx;
+            for _ in 0..abs_dx {
+                x = if dx < 0 { x - 1 } else { x + 1 };
+                if d < 0 {
+                    d += 2 * abs_dy
+                } else {
+                    y = if dy < 0 { y - 1 } else { y + 1 };
+                    d += 2 * abs_dy - 2 * abs_dx;
+                }
+                plot(x, y);
+            }
+        } else {
+            let mut d = 2 * abs_dx - abs_dy;
+            for _ in 0..abs_dy {
+                y = if dy < 0 { y - 1 } else { y + 1 };
+                if d < 0 {
+                    d += 2 * abs_dx
+                } else {
+                    x = if dx < 0 { x - 1 } else { x + 1 };
+                    d += 2 * abs_dx - 2 * abs_dy;
+                }
+                plot(x, y);
+            }
+        }
+    }
+}
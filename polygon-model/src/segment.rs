@@ -0,0 +1,431 @@
+use emath::{Pos2, Vec2};
+use serde::{Deserialize, Deserializer, Serialize};
+
+use crate::{bezier::BezierData, geometry::Geometry, point::Point};
+
+/// Circular arc between two points, described by the angle its tangent turns by
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct ArcData {
+    /// Positive sweep turns the same way as rotation from x axis to y axis
+    sweep: f32,
+}
+
+impl ArcData {
+    /// Full circle can't be described by its ends, so the sweep has to stay below it
+    const MAX_SWEEP: f32 = std::f32::consts::TAU - 0.01;
+    /// Arcs with smaller sweep are treated as straight lines
+    const MIN_SWEEP: f32 = 1e-4;
+
+    pub fn new(sweep: f32) -> Self {
+        Self {
+            sweep: sweep.clamp(-Self::MAX_SWEEP, Self::MAX_SWEEP),
+        }
+    }
+
+    pub fn radius(&self, start: Pos2, end: Pos2) -> f32 {
+        if self.sweep.abs() < Self::MIN_SWEEP {
+            return f32::INFINITY;
+        }
+        start.distance(end) / (2.0 * (self.sweep.abs() / 2.0).sin())
+    }
+
+    pub fn length(&self, start: Pos2, end: Pos2) -> f32 {
+        if self.sweep.abs() < Self::MIN_SWEEP {
+            return start.distance(end);
+        }
+        self.radius(start, end) * self.sweep.abs()
+    }
+
+    /// Returns point on the arc, t = 0 is the start and t = 1 is the end
+    pub fn point_at(&self, start: Pos2, end: Pos2, t: f32) -> Pos2 {
+        if self.sweep.abs() < Self::MIN_SWEEP {
+            return start.lerp(end, t);
+        }
+        let center = self.center(start, end);
+        center + Self::rotate(start - center, self.sweep * t)
+    }
+
+    /// Unit tangent at given point of the arc, in the direction from start to end
+    pub fn tangent_at(&self, start: Pos2, end: Pos2, t: f32) -> Vec2 {
+        let chord_direction = (end - start).normalized();
+        Self::rotate(chord_direction, self.sweep * (t - 0.5))
+    }
+
+    /// Returns control points of cubic curves approximating the arc, each spanning at most a quarter of a circle
+    pub fn to_cubics(self, start: Pos2, end: Pos2) -> Vec<[Pos2; 4]> {
+        if self.sweep.abs() < Self::MIN_SWEEP {
+            return vec![[
+                start,
+                start.lerp(end, 1.0 / 3.0),
+                start.lerp(end, 2.0 / 3.0),
+                end,
+            ]];
+        }
+        let count = (self.sweep.abs() / std::f32::consts::FRAC_PI_2)
+            .ceil()
+            .max(1.0) as usize;
+        let piece_sweep = self.sweep.abs() / count as f32;
+        let arm = 4.0 / 3.0 * (piece_sweep / 4.0).tan() * self.radius(start, end);
+        (0..count)
+            .map(|i| {
+                let t0 = i as f32 / count as f32;
+                let t1 = (i + 1) as f32 / count as f32;
+                // Ends of the arc are used directly, so that they exactly match the polygon vertices
+                let p0 = if i == 0 {
+                    start
+                } else {
+                    self.point_at(start, end, t0)
+                };
+                let p3 = if i + 1 == count {
+                    end
+                } else {
+                    self.point_at(start, end, t1)
+                };
+                [
+                    p0,
+                    p0 + self.tangent_at(start, end, t0) * arm,
+                    p3 - self.tangent_at(start, end, t1) * arm,
+                    p3,
+                ]
+            })
+            .collect()
+    }
+
+    /// Changes the sweep so that the arc starts in given direction
+    pub fn set_start_tangent(&mut self, start: Pos2, end: Pos2, tangent: Vec2) {
+        if tangent.length() > f32::EPSILON {
+            *self = Self::new(2.0 * Self::angle_between(tangent, end - start));
+        }
+    }
+
+    /// Changes the sweep so that the arc ends in given direction
+    pub fn set_end_tangent(&mut self, start: Pos2, end: Pos2, tangent: Vec2) {
+        if tangent.length() > f32::EPSILON {
+            *self = Self::new(2.0 * Self::angle_between(end - start, tangent));
+        }
+    }
+
+    /// Changes the sweep so that the middle of the arc lies as close as possible to given point
+    pub fn set_middle_point(&mut self, start: Pos2, end: Pos2, middle: Pos2) {
+        let chord = end - start;
+        let length = chord.length();
+        if length <= f32::EPSILON {
+            return;
+        }
+        // Middle of arcs with positive sweep lies on the right side of the chord
+        let normal = Vec2::new(chord.y, -chord.x) / length;
+        let sagitta = (middle - start.lerp(end, 0.5)).dot(normal);
+        *self = Self::new(4.0 * (2.0 * sagitta / length).atan());
+    }
+
+    fn center(&self, start: Pos2, end: Pos2) -> Pos2 {
+        let start_tangent = self.tangent_at(start, end, 0.0);
+        // Signed radius places the center on the side the arc turns to
+        let radius = start.distance(end) / (2.0 * (self.sweep / 2.0).sin());
+        start + Vec2::new(-start_tangent.y, start_tangent.x) * radius
+    }
+
+    fn rotate(v: Vec2, angle: f32) -> Vec2 {
+        let (sin, cos) = angle.sin_cos();
+        Vec2::new(v.x * cos - v.y * sin, v.x * sin + v.y * cos)
+    }
+
+    /// Signed angle of rotation from `a` to `b`
+    fn angle_between(a: Vec2, b: Vec2) -> f32 {
+        Geometry::cross(a, b).atan2(a.dot(b))
+    }
+}
+
+/// Geometry of the edge between two points
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub enum EdgeSegment {
+    #[default]
+    Line,
+    /// Quadratic bezier curve with its single control point
+    QuadraticBezier(Pos2),
+    CubicBezier(BezierData),
+    CircularArc(ArcData),
+}
+
+/// Segment as stored in documents, which were saved before other segments than cubic bezier
+/// ones were added with optional bezier data instead
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum StoredSegment {
+    Segment(EdgeSegment),
+    BezierData(Option<BezierData>),
+}
+
+impl EdgeSegment {
+    /// Reads the segment from document, older documents with optional bezier data are accepted too
+    pub(crate) fn deserialize_stored<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<Self, D::Error> {
+        Ok(match StoredSegment::deserialize(deserializer)? {
+            StoredSegment::Segment(segment) => segment,
+            StoredSegment::BezierData(Some(bezier_data)) => EdgeSegment::CubicBezier(bezier_data),
+            StoredSegment::BezierData(None) => EdgeSegment::Line,
+        })
+    }
+
+    pub fn is_curve(&self) -> bool {
+        !matches!(self, EdgeSegment::Line)
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            EdgeSegment::Line => "line",
+            EdgeSegment::QuadraticBezier(_) => "quadratic bezier segment",
+            EdgeSegment::CubicBezier(_) => "bezier segment",
+            EdgeSegment::CircularArc(_) => "arc",
+        }
+    }
+
+    pub fn control_points_count(&self) -> usize {
+        match self {
+            EdgeSegment::Line => 0,
+            EdgeSegment::QuadraticBezier(_) | EdgeSegment::CircularArc(_) => 1,
+            EdgeSegment::CubicBezier(_) => 2,
+        }
+    }
+
+    /// Returns points that user can drag to change shape of the segment
+    pub fn control_points(&self, start: &Point, end: &Point) -> Vec<Pos2> {
+        match self {
+            EdgeSegment::Line => vec![],
+            EdgeSegment::QuadraticBezier(control_point) => vec![*control_point],
+            EdgeSegment::CubicBezier(bd) => bd.inner_points().to_vec(),
+            EdgeSegment::CircularArc(arc) => vec![arc.point_at(*start.pos(), *end.pos(), 0.5)],
+        }
+    }
+
+    pub fn update_control_point(&mut self, start: &Point, end: &Point, index: usize, pos: Pos2) {
+        match self {
+            EdgeSegment::Line => eprintln!("Trying to move control point of a line"),
+            EdgeSegment::QuadraticBezier(control_point) => *control_point = pos,
+            EdgeSegment::CubicBezier(bd) => bd.update_inner_point_position(index, pos),
+            EdgeSegment::CircularArc(arc) => arc.set_middle_point(*start.pos(), *end.pos(), pos),
+        }
+    }
+
+    /// Returns point which together with start of the segment gives its derivative at the start,
+    /// after dividing by `derivative_factor`. For a line it's just its end.
+    pub fn start_handle(&self, start: &Point, end: &Point) -> Pos2 {
+        match self {
+            EdgeSegment::Line => *end.pos(),
+            EdgeSegment::QuadraticBezier(control_point) => *control_point,
+            EdgeSegment::CubicBezier(bd) => bd.inner_points()[0],
+            EdgeSegment::CircularArc(arc) => {
+                let (start, end) = (*start.pos(), *end.pos());
+                start + arc.tangent_at(start, end, 0.0) * arc.length(start, end)
+            }
+        }
+    }
+
+    /// Same as `start_handle`, but for the end of the segment. For a line it's its start.
+    pub fn end_handle(&self, start: &Point, end: &Point) -> Pos2 {
+        match self {
+            EdgeSegment::Line => *start.pos(),
+            EdgeSegment::QuadraticBezier(control_point) => *control_point,
+            EdgeSegment::CubicBezier(bd) => bd.inner_points()[1],
+            EdgeSegment::CircularArc(arc) => {
+                let (start, end) = (*start.pos(), *end.pos());
+                end - arc.tangent_at(start, end, 1.0) * arc.length(start, end)
+            }
+        }
+    }
+
+    /// Moves the start handle. Arcs can only change their direction, so for them only direction
+    /// of the handle matters. Lines have no handles, their ends have to be moved instead.
+    pub fn set_start_handle(&mut self, start: &Point, end: &Point, pos: Pos2) {
+        match self {
+            EdgeSegment::Line => eprintln!("Trying to move handle of a line"),
+            EdgeSegment::QuadraticBezier(control_point) => *control_point = pos,
+            EdgeSegment::CubicBezier(bd) => bd.update_inner_point_position(0, pos),
+            EdgeSegment::CircularArc(arc) => {
+                arc.set_start_tangent(*start.pos(), *end.pos(), pos - *start.pos())
+            }
+        }
+    }
+
+    /// Same as `set_start_handle`, but for the end of the segment
+    pub fn set_end_handle(&mut self, start: &Point, end: &Point, pos: Pos2) {
+        match self {
+            EdgeSegment::Line => eprintln!("Trying to move handle of a line"),
+            EdgeSegment::QuadraticBezier(control_point) => *control_point = pos,
+            EdgeSegment::CubicBezier(bd) => bd.update_inner_point_position(1, pos),
+            EdgeSegment::CircularArc(arc) => {
+                arc.set_end_tangent(*start.pos(), *end.pos(), *end.pos() - pos)
+            }
+        }
+    }
+
+    /// Ratio of the derivative at the segment end to the vector between the end and its handle
+    pub fn derivative_factor(&self) -> f32 {
+        match self {
+            EdgeSegment::Line => 1.0,
+            EdgeSegment::QuadraticBezier(_) => 2.0,
+            EdgeSegment::CubicBezier(_) => 3.0,
+            EdgeSegment::CircularArc(_) => 1.0,
+        }
+    }
+
+    /// Returns control points of cubic curves that together describe the segment
+    pub fn to_cubics(self, start: &Point, end: &Point) -> Vec<[Pos2; 4]> {
+        let (start, end) = (*start.pos(), *end.pos());
+        match self {
+            EdgeSegment::Line => vec![[
+                start,
+                start.lerp(end, 1.0 / 3.0),
+                start.lerp(end, 2.0 / 3.0),
+                end,
+            ]],
+            EdgeSegment::QuadraticBezier(control_point) => vec![[
+                start,
+                start.lerp(control_point, 2.0 / 3.0),
+                end.lerp(control_point, 2.0 / 3.0),
+                end,
+            ]],
+            EdgeSegment::CubicBezier(bd) => {
+                vec![[start, bd.inner_points()[0], bd.inner_points()[1], end]]
+            }
+            EdgeSegment::CircularArc(arc) => arc.to_cubics(start, end),
+        }
+    }
+
+    /// Returns points on the segment such that polyline connecting them (together with `end`)
+    /// is never further than `tolerance` from it. `start` and `end` are not included.
+    pub fn flatten(&self, start: &Point, end: &Point, tolerance: f32) -> Vec<Pos2> {
+        if !self.is_curve() {
+            return vec![];
+        }
+        let cubics = self.to_cubics(start, end);
+        let mut flattened = vec![];
+        for (id, control_points) in cubics.iter().enumerate() {
+            flattened.extend(BezierData::flatten_control_polygon(
+                control_points,
+                tolerance,
+            ));
+            if id + 1 < cubics.len() {
+                flattened.push(control_points[3]);
+            }
+        }
+        flattened
+    }
+
+    /// Returns length of the segment measured along it
+    pub fn length(&self, start: &Point, end: &Point) -> f32 {
+        match self {
+            EdgeSegment::Line => start.pos().distance(*end.pos()),
+            EdgeSegment::CircularArc(arc) => arc.length(*start.pos(), *end.pos()),
+            // Both kinds of bezier segments are described by a single cubic
+            EdgeSegment::QuadraticBezier(_) | EdgeSegment::CubicBezier(_) => {
+                BezierData::arc_length(&self.to_cubics(start, end)[0], 1.0)
+            }
+        }
+    }
+
+    /// Returns `count` points lying on the segment at equal distances (measured along it), including its ends.
+    /// Single point is the start of the segment.
+    pub fn evenly_spaced_points(&self, start: &Point, end: &Point, count: usize) -> Vec<Pos2> {
+        let step = 1.0 / count.saturating_sub(1).max(1) as f32;
+        match self {
+            EdgeSegment::Line => (0..count)
+                .map(|i| start.pos().lerp(*end.pos(), i as f32 * step))
+                .collect(),
+            // Points of an arc are already spread evenly along its parameter
+            EdgeSegment::CircularArc(arc) => (0..count)
+                .map(|i| arc.point_at(*start.pos(), *end.pos(), i as f32 * step))
+                .collect(),
+            EdgeSegment::QuadraticBezier(_) | EdgeSegment::CubicBezier(_) => {
+                BezierData::evenly_spaced_points(&self.to_cubics(start, end)[0], count)
+            }
+        }
+    }
+
+    /// Returns polyline approximating the segment (from `start` to `end`), that is never further
+    /// than `tolerance` from it, used for drawing
+    pub fn get_curve_points(&self, start: &Point, end: &Point, tolerance: f32) -> Vec<Pos2> {
+        std::iter::once(*start.pos())
+            .chain(self.flatten(start, end, tolerance))
+            .chain(std::iter::once(*end.pos()))
+            .collect()
+    }
+
+    /// Checks whether point is not further than `tolerance` from the segment
+    pub fn contains_point(&self, start: &Point, end: &Point, point: Pos2, tolerance: f32) -> bool {
+        self.get_curve_points(start, end, tolerance / 4.0)
+            .windows(2)
+            .any(|w| Geometry::distance_to_segment(point, w[0], w[1]) <= tolerance)
+    }
+
+    pub fn translate(&mut self, diff: Vec2) {
+        match self {
+            EdgeSegment::Line | EdgeSegment::CircularArc(_) => {}
+            EdgeSegment::QuadraticBezier(control_point) => *control_point += diff,
+            EdgeSegment::CubicBezier(bd) => {
+                bd.inner_points_mut()[0] += diff;
+                bd.inner_points_mut()[1] += diff;
+            }
+        }
+    }
+
+    /// Scales control points of the segment by `factor` around `center`
+    pub fn scale(&mut self, center: Pos2, factor: f32) {
+        let scale_point = |point: &mut Pos2| *point = center + (*point - center) * factor;
+        match self {
+            EdgeSegment::Line | EdgeSegment::CircularArc(_) => {}
+            EdgeSegment::QuadraticBezier(control_point) => scale_point(control_point),
+            EdgeSegment::CubicBezier(bd) => bd.inner_points_mut().iter_mut().for_each(scale_point),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use emath::pos2;
+
+    use super::*;
+
+    #[test]
+    fn evenly_spaced_points_handle_any_count() {
+        let start = Point::new(pos2(0.0, 0.0));
+        let end = Point::new(pos2(90.0, 0.0));
+        for segment in [
+            EdgeSegment::Line,
+            EdgeSegment::QuadraticBezier(pos2(45.0, 30.0)),
+            EdgeSegment::CircularArc(ArcData::new(1.0)),
+        ] {
+            assert!(segment.evenly_spaced_points(&start, &end, 0).is_empty());
+            let single = segment.evenly_spaced_points(&start, &end, 1);
+            assert_eq!(single.len(), 1);
+            assert!(single[0].distance(*start.pos()) < 0.01);
+            let points = segment.evenly_spaced_points(&start, &end, 4);
+            assert_eq!(points.len(), 4);
+            assert!(points[3].distance(*end.pos()) < 0.01);
+        }
+    }
+
+    #[test]
+    fn length_of_half_circle_arc() {
+        let start = Point::new(pos2(0.0, 0.0));
+        let end = Point::new(pos2(100.0, 0.0));
+        let arc = EdgeSegment::CircularArc(ArcData::new(std::f32::consts::PI));
+        assert!((arc.length(&start, &end) - 50.0 * std::f32::consts::PI).abs() < 0.1);
+        assert!((EdgeSegment::Line.length(&start, &end) - 100.0).abs() < 1e-4);
+    }
+
+    #[test]
+    fn stored_segment_accepts_old_bezier_data() {
+        let point: Point = serde_json::from_str(
+            r#"{"pos":{"x":0,"y":0},"constraint":null,"bezier_data":null,"continuity_type":"C1"}"#,
+        )
+        .unwrap();
+        assert!(matches!(point.segment(), EdgeSegment::Line));
+        let point: Point = serde_json::from_str(
+            r#"{"pos":{"x":0,"y":0},"constraint":null,"segment":{"QuadraticBezier":{"x":5,"y":5}},"continuity_type":"C1"}"#,
+        )
+        .unwrap();
+        assert!(matches!(point.segment(), EdgeSegment::QuadraticBezier(_)));
+    }
+}
@@ -0,0 +1,79 @@
+use emath::{Pos2, Vec2};
+use serde::{Deserialize, Serialize};
+
+use crate::point::Point;
+
+/// Polygon with optional holes inside it, or an open path
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Shape {
+    /// Outer boundary of the shape
+    outline: Vec<Point>,
+    /// Boundaries of the holes, each of them lies inside the outline
+    holes: Vec<Vec<Point>>,
+    /// Whether the last point of the outline is connected with the first one.
+    /// Open paths have no holes and enclose no area.
+    #[serde(default = "Shape::closed_by_default")]
+    closed: bool,
+}
+
+impl Shape {
+    pub fn new(outline: Vec<Point>, holes: Vec<Vec<Point>>) -> Self {
+        Self {
+            outline,
+            holes,
+            closed: true,
+        }
+    }
+
+    pub fn new_open(outline: Vec<Point>) -> Self {
+        Self {
+            outline,
+            holes: vec![],
+            closed: false,
+        }
+    }
+
+    fn closed_by_default() -> bool {
+        true
+    }
+
+    pub fn is_closed(&self) -> bool {
+        self.closed
+    }
+
+    pub fn outline(&self) -> &Vec<Point> {
+        &self.outline
+    }
+
+    pub fn holes(&self) -> &Vec<Vec<Point>> {
+        &self.holes
+    }
+
+    pub fn into_parts(self) -> (Vec<Point>, Vec<Vec<Point>>) {
+        (self.outline, self.holes)
+    }
+
+    /// Checks whether the shape has enough points to be edited,
+    /// at least 3 in every ring of a polygon and 2 in an open path
+    pub fn is_valid(&self) -> bool {
+        let min_points_count = if self.closed { 3 } else { 2 };
+        std::iter::once(&self.outline)
+            .chain(self.holes.iter())
+            .all(|ring| ring.len() >= min_points_count)
+    }
+
+    /// Moves all points of the shape, together with control points of its curves
+    pub fn translate(&mut self, diff: Vec2) {
+        for ring in std::iter::once(&mut self.outline).chain(self.holes.iter_mut()) {
+            Point::update_position_all(ring, diff);
+        }
+    }
+
+    /// Returns all boundaries of the shape (outline first) with bezier segments replaced by polylines
+    pub fn flattened_rings(&self, tolerance: f32) -> Vec<Vec<Pos2>> {
+        std::iter::once(&self.outline)
+            .chain(self.holes.iter())
+            .map(|ring| Point::flatten(ring, tolerance))
+            .collect()
+    }
+}
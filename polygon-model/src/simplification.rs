@@ -0,0 +1,85 @@
+use crate::{geometry::Geometry, point::Point};
+
+/// Removes vertices of the polygon with Ramer-Douglas-Peucker algorithm.
+///
+/// Vertices adjacent to edges with constraint or curve are never removed, so such edges
+/// stay untouched. The ring is split in them into chains, each of which is simplified separately.
+pub struct Simplification;
+
+impl Simplification {
+    pub fn simplify(points: &[Point], tolerance: f32) -> Vec<Point> {
+        if points.len() <= 3 {
+            return points.to_vec();
+        }
+        let mut anchors: Vec<usize> = (0..points.len())
+            .filter(|id| Self::is_locked(points, *id))
+            .collect();
+        if anchors.is_empty() {
+            // Without locked vertices the ring is split into two chains in the first point
+            // and the point furthest from it
+            let furthest = (1..points.len())
+                .max_by(|a, b| {
+                    let distance = |id: &usize| points[*id].pos().distance(*points[0].pos());
+                    distance(a).total_cmp(&distance(b))
+                })
+                .unwrap();
+            anchors = vec![0, furthest];
+        }
+
+        let mut kept = vec![false; points.len()];
+        for (anchor_id, anchor) in anchors.iter().enumerate() {
+            let next_anchor = anchors[(anchor_id + 1) % anchors.len()];
+            let chain_length = (next_anchor + points.len() - anchor - 1) % points.len() + 1;
+            let chain: Vec<usize> = (0..=chain_length)
+                .map(|i| (anchor + i) % points.len())
+                .collect();
+            Self::simplify_chain(points, &chain, tolerance, &mut kept);
+        }
+
+        let simplified: Vec<Point> = points
+            .iter()
+            .zip(kept)
+            .filter_map(|(point, kept)| kept.then_some(*point))
+            .collect();
+        if simplified.len() < 3 {
+            return points.to_vec();
+        }
+        simplified
+    }
+
+    /// Vertex is locked when any of its edges has constraint or is a curve
+    fn is_locked(points: &[Point], id: usize) -> bool {
+        let previous = &points[Point::get_previous_index(points, id)];
+        let current = &points[id];
+        [previous, current]
+            .iter()
+            .any(|point| point.constraint().is_some() || point.is_start_of_curve_segment())
+    }
+
+    /// Marks vertices of the chain (given by ids of points) that should be kept
+    fn simplify_chain(points: &[Point], chain: &[usize], tolerance: f32, kept: &mut [bool]) {
+        kept[chain[0]] = true;
+        kept[chain[chain.len() - 1]] = true;
+        let mut ranges = vec![(0, chain.len() - 1)];
+        while let Some((start, end)) = ranges.pop() {
+            if end - start < 2 {
+                continue;
+            }
+            let start_pos = *points[chain[start]].pos();
+            let end_pos = *points[chain[end]].pos();
+            let (furthest, distance) = (start + 1..end)
+                .map(|i| {
+                    let distance =
+                        Geometry::distance_to_segment(*points[chain[i]].pos(), start_pos, end_pos);
+                    (i, distance)
+                })
+                .max_by(|a, b| a.1.total_cmp(&b.1))
+                .unwrap();
+            if distance > tolerance {
+                kept[chain[furthest]] = true;
+                ranges.push((start, furthest));
+                ranges.push((furthest, end));
+            }
+        }
+    }
+}
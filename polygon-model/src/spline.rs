@@ -0,0 +1,125 @@
+use emath::{Pos2, Vec2};
+
+use crate::{
+    bezier::BezierData,
+    point::{ContinuityType, Point},
+    segment::EdgeSegment,
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SplineKind {
+    /// Vertices are control points of the spline, which passes near them
+    BSpline,
+    /// Spline passes through all vertices
+    CatmullRom,
+}
+
+impl SplineKind {
+    pub fn name(&self) -> &'static str {
+        match self {
+            SplineKind::BSpline => "B-spline",
+            SplineKind::CatmullRom => "Catmull-Rom spline",
+        }
+    }
+}
+
+/// Closed uniform cubic splines described by the polygon vertices
+pub struct Spline;
+
+impl Spline {
+    /// Returns control points of cubic bezier curves that together make the closed spline,
+    /// the i-th of them corresponds to the edge starting in the i-th vertex
+    pub fn to_cubics(points: &[Point], kind: SplineKind) -> Vec<[Pos2; 4]> {
+        if points.len() < 3 {
+            return vec![];
+        }
+        (0..points.len())
+            .map(|id| {
+                let id_previous = Point::get_previous_index(points, id);
+                let id_next = Point::get_next_index(points, id);
+                let id_after_next = Point::get_next_index(points, id_next);
+                let [p0, p1, p2, p3] =
+                    [id_previous, id, id_next, id_after_next].map(|id| points[id].pos().to_vec2());
+                let control_points = match kind {
+                    SplineKind::BSpline => [
+                        (p0 + 4.0 * p1 + p2) / 6.0,
+                        (2.0 * p1 + p2) / 3.0,
+                        (p1 + 2.0 * p2) / 3.0,
+                        (p1 + 4.0 * p2 + p3) / 6.0,
+                    ],
+                    // Tangent in each vertex is half of the vector between its neighbours
+                    SplineKind::CatmullRom => [p1, p1 + (p2 - p0) / 6.0, p2 - (p3 - p1) / 6.0, p2],
+                };
+                control_points.map(|control_point| control_point.to_pos2())
+            })
+            .collect()
+    }
+
+    /// Returns polygon made of explicit bezier segments that describe the same spline.
+    /// B-spline is C2 continuous and Catmull-Rom spline is C1 continuous in all joints.
+    pub fn to_bezier_polygon(points: &[Point], kind: SplineKind) -> Vec<Point> {
+        let continuity = match kind {
+            SplineKind::BSpline => ContinuityType::C2,
+            SplineKind::CatmullRom => ContinuityType::C1,
+        };
+        Self::to_cubics(points, kind)
+            .into_iter()
+            .map(|[start, first_inner, second_inner, _]| {
+                Point::new_all(
+                    start,
+                    None,
+                    EdgeSegment::CubicBezier(BezierData::new([first_inner, second_inner])),
+                    continuity,
+                )
+            })
+            .collect()
+    }
+
+    /// Returns polygon in which every edge without constraint is a cubic bezier segment, and all
+    /// vertices have C1 continuity. Tangents are taken from cardinal spline going through the vertices:
+    /// tension 0 gives Catmull-Rom spline and tension 1 gives straight edges.
+    /// Edges with constraints are left unchanged, tangents next to them follow these edges.
+    pub fn smooth(points: &[Point], tension: f32) -> Vec<Point> {
+        if points.len() < 3 {
+            return points.to_vec();
+        }
+        let tangents: Vec<Vec2> = (0..points.len())
+            .map(|id| Self::smoothing_tangent(points, id, tension))
+            .collect();
+        (0..points.len())
+            .map(|id| {
+                let id_next = Point::get_next_index(points, id);
+                let mut point = points[id];
+                if !point.has_constraint() {
+                    let start = *points[id].pos();
+                    let end = *points[id_next].pos();
+                    point.init_bezier_data([
+                        start + tangents[id] / 3.0,
+                        end - tangents[id_next] / 3.0,
+                    ]);
+                }
+                point.apply_C1();
+                point
+            })
+            .collect()
+    }
+
+    /// Returns derivative the smoothed curve should have in the vertex
+    fn smoothing_tangent(points: &[Point], id: usize, tension: f32) -> Vec2 {
+        let id_previous = Point::get_previous_index(points, id);
+        let id_next = Point::get_next_index(points, id);
+        let (previous, current, next) = (&points[id_previous], &points[id], &points[id_next]);
+        // C1 continuity requires the same derivative as the one of unchanged edge
+        if previous.has_constraint() {
+            let segment = previous.segment();
+            return (*current.pos() - segment.end_handle(previous, current))
+                * segment.derivative_factor();
+        }
+        if current.has_constraint() {
+            let segment = current.segment();
+            return (segment.start_handle(current, next) - *current.pos())
+                * segment.derivative_factor();
+        }
+        (1.0 - tension) * (*next.pos() - *previous.pos()) / 2.0
+    }
+}
@@ -0,0 +1,379 @@
+use std::fmt::Write;
+
+use emath::{Pos2, Rect, Vec2};
+
+use crate::{
+    document::DocumentError, geometry::Geometry, point::Point, segment::EdgeSegment, shape::Shape,
+};
+
+#[derive(Debug, Clone, Copy)]
+enum Token {
+    Command(char),
+    Number(f32),
+}
+
+/// Reading and writing shapes as SVG paths. Only geometry is kept, constraints are lost and every
+/// vertex read from SVG has `G0` continuity, so that curves keep their shape.
+pub struct Svg;
+
+impl Svg {
+    /// Empty space around the shapes
+    const MARGIN: f32 = 10.0;
+    /// Tolerance of polylines used only to find the area covered by the shapes
+    const BOUNDS_TOLERANCE: f32 = 1.0;
+
+    /// Returns SVG document with one path for each shape, holes are subpaths of the path
+    pub fn write(shapes: &[Shape]) -> String {
+        let points: Vec<Pos2> = shapes
+            .iter()
+            .flat_map(|shape| shape.flattened_rings(Self::BOUNDS_TOLERANCE))
+            .flatten()
+            .collect();
+        let bounds = if points.is_empty() {
+            Rect::ZERO
+        } else {
+            Rect::from_points(&points)
+        }
+        .expand(Self::MARGIN);
+        let mut svg = format!(
+            "<svg xmlns=\"http://www.w3.org/2000/svg\" viewBox=\"{} {} {} {}\" width=\"{}\" height=\"{}\">\n",
+            bounds.min.x,
+            bounds.min.y,
+            bounds.width(),
+            bounds.height(),
+            bounds.width(),
+            bounds.height()
+        );
+        for shape in shapes {
+            // Rings without points have nothing to draw
+            let data: Vec<String> = std::iter::once(shape.outline())
+                .chain(shape.holes())
+                .filter(|ring| !ring.is_empty())
+                .map(|ring| Self::path_data(ring, shape.is_closed()))
+                .collect();
+            if data.is_empty() {
+                continue;
+            }
+            let fill = if shape.is_closed() { "#c8e6c9" } else { "none" };
+            let _ = writeln!(
+                svg,
+                "  <path d=\"{}\" fill=\"{}\" fill-rule=\"evenodd\" stroke=\"black\"/>",
+                data.join(" "),
+                fill
+            );
+        }
+        svg.push_str("</svg>\n");
+        svg
+    }
+
+    fn path_data(ring: &[Point], closed: bool) -> String {
+        let mut data = format!("M {} {}", ring[0].pos().x, ring[0].pos().y);
+        for id in 0..Point::edges_count(ring, closed) {
+            let (start, end) = (&ring[id], &ring[Point::get_next_index(ring, id)]);
+            let _ =
+                match start.segment() {
+                    EdgeSegment::Line => write!(data, " L {} {}", end.pos().x, end.pos().y),
+                    EdgeSegment::QuadraticBezier(control_point) => write!(
+                        data,
+                        " Q {} {} {} {}",
+                        control_point.x,
+                        control_point.y,
+                        end.pos().x,
+                        end.pos().y
+                    ),
+                    // Arcs are written as cubic curves, which are read back as such
+                    segment => segment.to_cubics(start, end).iter().try_for_each(
+                        |[_, first, second, end]| {
+                            write!(
+                                data,
+                                " C {} {} {} {} {} {}",
+                                first.x, first.y, second.x, second.y, end.x, end.y
+                            )
+                        },
+                    ),
+                };
+        }
+        if closed {
+            data.push_str(" Z");
+        }
+        data
+    }
+
+    /// Reads shapes from all paths of SVG document. Closed subpaths of a path become a polygon
+    /// (the first one is its outline and the others its holes), open ones become open paths.
+    pub fn read(svg: &str) -> Result<Vec<Shape>, DocumentError> {
+        let mut shapes = vec![];
+        let mut rest = svg;
+        while let Some(start) = rest.find("<path") {
+            let tag = &rest[start..];
+            let end = tag
+                .find('>')
+                .ok_or_else(|| DocumentError::Svg("unterminated path element".to_string()))?;
+            if let Some(data) = Self::attribute(&tag[..end], "d") {
+                shapes.extend(Self::read_path(data)?);
+            }
+            rest = &tag[end..];
+        }
+        Ok(shapes)
+    }
+
+    /// Returns value of the attribute of an element, given as text of its opening tag
+    fn attribute<'a>(tag: &'a str, name: &str) -> Option<&'a str> {
+        let pattern = format!("{}=", name);
+        let mut rest = tag;
+        while let Some(id) = rest.find(&pattern) {
+            let after = &rest[id + pattern.len()..];
+            if rest[..id].ends_with(char::is_whitespace) {
+                let quote = after.chars().next()?;
+                if quote == '"' || quote == '\'' {
+                    let value = &after[1..];
+                    return value.find(quote).map(|end| &value[..end]);
+                }
+            }
+            rest = after;
+        }
+        None
+    }
+
+    fn read_path(data: &str) -> Result<Vec<Shape>, DocumentError> {
+        let tokens = Self::tokenize(data)?;
+        let mut closed_rings: Vec<Vec<Point>> = vec![];
+        let mut open_rings: Vec<Vec<Point>> = vec![];
+        let mut ring: Vec<Point> = vec![];
+        let mut pos = Pos2::ZERO;
+        // First point of the current subpath, where drawing continues after it's closed
+        let mut subpath_start = None;
+        let mut command = None;
+        let mut id = 0;
+        while id < tokens.len() {
+            if let Token::Command(c) = tokens[id] {
+                id += 1;
+                command = Some(c);
+                if c == 'Z' || c == 'z' {
+                    Self::close_ring(&mut ring, &mut closed_rings);
+                    pos = subpath_start.unwrap_or(pos);
+                    command = None;
+                }
+                continue;
+            }
+            let c = command.ok_or_else(|| {
+                DocumentError::Svg("expected path command before numbers".to_string())
+            })?;
+            // Drawing right after closing a subpath starts a new one in the same point
+            if ring.is_empty() && !matches!(c, 'M' | 'm') {
+                let start = subpath_start.ok_or_else(|| {
+                    DocumentError::Svg("path has to start with move command".to_string())
+                })?;
+                ring.push(Self::new_vertex(start));
+            }
+            let origin = if c.is_ascii_lowercase() {
+                pos.to_vec2()
+            } else {
+                Vec2::ZERO
+            };
+            match c.to_ascii_uppercase() {
+                'M' => {
+                    let [x, y] = Self::numbers(&tokens, &mut id)?;
+                    if ring.len() > 1 {
+                        open_rings.push(std::mem::take(&mut ring));
+                    }
+                    pos = Pos2::new(x, y) + origin;
+                    subpath_start = Some(pos);
+                    ring = vec![Self::new_vertex(pos)];
+                    // Following pairs of numbers are lines
+                    command = Some(if c == 'm' { 'l' } else { 'L' });
+                    continue;
+                }
+                'L' => {
+                    let [x, y] = Self::numbers(&tokens, &mut id)?;
+                    pos = Pos2::new(x, y) + origin;
+                }
+                'H' => {
+                    let [x] = Self::numbers(&tokens, &mut id)?;
+                    pos.x = x + origin.x;
+                }
+                'V' => {
+                    let [y] = Self::numbers(&tokens, &mut id)?;
+                    pos.y = y + origin.y;
+                }
+                'Q' => {
+                    let [cx, cy, x, y] = Self::numbers(&tokens, &mut id)?;
+                    Self::last_vertex(&mut ring)
+                        .init_quadratic_bezier_data(Pos2::new(cx, cy) + origin);
+                    pos = Pos2::new(x, y) + origin;
+                }
+                'C' => {
+                    let [x1, y1, x2, y2, x, y] = Self::numbers(&tokens, &mut id)?;
+                    Self::last_vertex(&mut ring)
+                        .init_bezier_data([Pos2::new(x1, y1) + origin, Pos2::new(x2, y2) + origin]);
+                    pos = Pos2::new(x, y) + origin;
+                }
+                _ => {
+                    return Err(DocumentError::Svg(format!(
+                        "unsupported path command {}",
+                        c
+                    )))
+                }
+            }
+            ring.push(Self::new_vertex(pos));
+        }
+        if ring.len() > 1 {
+            open_rings.push(ring);
+        }
+        let mut shapes: Vec<Shape> = vec![];
+        if !closed_rings.is_empty() {
+            let outline = closed_rings.remove(0);
+            shapes.push(Shape::new(outline, closed_rings));
+        }
+        shapes.extend(open_rings.into_iter().map(Shape::new_open));
+        Ok(shapes)
+    }
+
+    fn new_vertex(pos: Pos2) -> Point {
+        let mut point = Point::new(pos);
+        point.apply_G0();
+        point
+    }
+
+    fn last_vertex(ring: &mut [Point]) -> &mut Point {
+        ring.last_mut()
+            .expect("Subpath is started before drawing commands")
+    }
+
+    /// Ends the subpath. The last vertex is dropped when it repeats the first one, as the edge
+    /// to it already closes the ring.
+    fn close_ring(ring: &mut Vec<Point>, closed_rings: &mut Vec<Vec<Point>>) {
+        if ring.len() > 1
+            && ring[0].pos().distance(*ring[ring.len() - 1].pos()) < Geometry::SAME_POINT_TOLERANCE
+        {
+            ring.pop();
+        }
+        if !ring.is_empty() {
+            closed_rings.push(std::mem::take(ring));
+        }
+    }
+
+    fn numbers<const N: usize>(
+        tokens: &[Token],
+        id: &mut usize,
+    ) -> Result<[f32; N], DocumentError> {
+        let mut numbers = [0.0; N];
+        for number in numbers.iter_mut() {
+            match tokens.get(*id) {
+                Some(Token::Number(value)) => *number = *value,
+                _ => {
+                    return Err(DocumentError::Svg(
+                        "path command is missing coordinates".to_string(),
+                    ))
+                }
+            }
+            *id += 1;
+        }
+        Ok(numbers)
+    }
+
+    /// Splits path data into commands and numbers, which can be separated by whitespace, commas,
+    /// or nothing when the next number starts with a sign or a second dot
+    fn tokenize(data: &str) -> Result<Vec<Token>, DocumentError> {
+        let mut tokens = vec![];
+        let chars: Vec<char> = data.chars().collect();
+        let mut id = 0;
+        while id < chars.len() {
+            let c = chars[id];
+            if c.is_whitespace() || c == ',' {
+                id += 1;
+            } else if c.is_ascii_alphabetic() && c != 'e' && c != 'E' {
+                tokens.push(Token::Command(c));
+                id += 1;
+            } else {
+                let start = id;
+                let mut seen_dot = false;
+                if chars[id] == '-' || chars[id] == '+' {
+                    id += 1;
+                }
+                while id < chars.len() {
+                    match chars[id] {
+                        '0'..='9' => id += 1,
+                        '.' if !seen_dot => {
+                            seen_dot = true;
+                            id += 1;
+                        }
+                        'e' | 'E' => {
+                            id += 1;
+                            if id < chars.len() && (chars[id] == '-' || chars[id] == '+') {
+                                id += 1;
+                            }
+                        }
+                        _ => break,
+                    }
+                }
+                let text: String = chars[start..id].iter().collect();
+                let number = text.parse().map_err(|_| {
+                    DocumentError::Svg(format!("invalid number {:?} in path data", text))
+                })?;
+                tokens.push(Token::Number(number));
+            }
+        }
+        Ok(tokens)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use emath::pos2;
+
+    use super::*;
+    use crate::{bezier::BezierData, point::fixtures};
+
+    fn same_points(read: &[Point], written: &[Point]) -> bool {
+        read.len() == written.len()
+            && read
+                .iter()
+                .zip(written)
+                .all(|(a, b)| a.pos().distance(*b.pos()) < 0.01)
+    }
+
+    #[test]
+    fn read_returns_written_shapes() {
+        let mut outline = fixtures::square(0.0, 0.0, 100.0);
+        outline[0].init_bezier_data([pos2(30.0, -20.0), pos2(70.0, -20.0)]);
+        let hole = fixtures::points(&[(40.0, 40.0), (60.0, 40.0), (50.0, 60.0)]);
+        let path = fixtures::points(&[(200.0, 0.0), (250.0, 50.0)]);
+        let shapes = vec![
+            Shape::new(outline.clone(), vec![hole.clone()]),
+            Shape::new_open(path.clone()),
+        ];
+
+        let read = Svg::read(&Svg::write(&shapes)).unwrap();
+        assert_eq!(read.len(), 2);
+        assert!(read[0].is_closed() && !read[1].is_closed());
+        assert!(same_points(read[0].outline(), &outline));
+        assert!(same_points(&read[0].holes()[0], &hole));
+        assert!(same_points(read[1].outline(), &path));
+        let EdgeSegment::CubicBezier(bezier_data) = read[0].outline()[0].segment() else {
+            panic!("Curve was not read back");
+        };
+        let written = BezierData::new([pos2(30.0, -20.0), pos2(70.0, -20.0)]);
+        for (a, b) in bezier_data
+            .inner_points()
+            .iter()
+            .zip(written.inner_points())
+        {
+            assert!(a.distance(*b) < 0.01);
+        }
+    }
+
+    #[test]
+    fn read_continues_from_closed_subpath() {
+        let shapes = Svg::read(r#"<path d="M0 0 L100 0 L50 80 Z L10 10"/>"#).unwrap();
+        assert_eq!(shapes.len(), 2);
+        assert_eq!(*shapes[1].outline()[0].pos(), pos2(0.0, 0.0));
+        assert!(Svg::read(r#"<path d="L10 10"/>"#).is_err());
+    }
+
+    #[test]
+    fn write_skips_shapes_without_points() {
+        let svg = Svg::write(&[Shape::new(vec![], vec![])]);
+        assert!(!svg.contains("<path"));
+    }
+}
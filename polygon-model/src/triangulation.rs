@@ -0,0 +1,201 @@
+use emath::Pos2;
+
+use crate::{geometry::Geometry, shape::Shape};
+
+/// Splits shapes into triangles with ear clipping.
+///
+/// Holes are first connected with the outline by bridge edges going to the closest visible vertex,
+/// so that the whole shape is described by a single ring. Then, in each step, a convex vertex whose
+/// triangle contains no other vertex (an ear) is cut off. Degenerate rings never cause a panic,
+/// they just produce fewer triangles.
+pub struct Triangulation;
+
+impl Triangulation {
+    const EPS: f32 = 1e-4;
+
+    /// Curves are replaced with polylines not further than `tolerance` from them
+    pub fn triangulate(shape: &Shape, tolerance: f32) -> Vec<[Pos2; 3]> {
+        let mut rings = shape.flattened_rings(tolerance);
+        for ring in rings.iter_mut() {
+            ring.dedup_by(|a, b| a.distance(*b) <= Self::EPS);
+            Geometry::remove_collinear_points(ring, Self::EPS);
+        }
+        rings.retain(|ring| ring.len() >= 3 && Geometry::signed_area(ring).abs() > Self::EPS);
+        if rings.is_empty() {
+            return vec![];
+        }
+
+        // Outline goes counterclockwise, holes clockwise
+        let mut outline = rings.remove(0);
+        if Geometry::signed_area(&outline) < 0.0 {
+            outline.reverse();
+        }
+        let mut holes = rings;
+        for hole in holes.iter_mut() {
+            if Geometry::signed_area(hole) > 0.0 {
+                hole.reverse();
+            }
+        }
+
+        // Holes that are further to the right are bridged first, so that bridges don't cross each other
+        holes.sort_by(|a, b| Self::max_x(b).total_cmp(&Self::max_x(a)));
+        for id in 0..holes.len() {
+            Self::bridge_hole(&mut outline, &holes[id], &holes[id + 1..]);
+        }
+
+        Self::clip_ears(outline)
+    }
+
+    fn max_x(ring: &[Pos2]) -> f32 {
+        ring.iter().map(|p| p.x).fold(f32::MIN, f32::max)
+    }
+
+    /// Connects hole with the outline by two overlapping edges, making it a part of the outline
+    fn bridge_hole(outline: &mut Vec<Pos2>, hole: &[Pos2], other_holes: &[Vec<Pos2>]) {
+        let hole_start = (0..hole.len())
+            .max_by(|a, b| hole[*a].x.total_cmp(&hole[*b].x))
+            .unwrap();
+        let hole_point = hole[hole_start];
+
+        let crosses_any_edge = |from: Pos2, to: Pos2| {
+            std::iter::once(&outline[..])
+                .chain(std::iter::once(hole))
+                .chain(other_holes.iter().map(|h| &h[..]))
+                .any(|ring| {
+                    (0..ring.len()).any(|id| {
+                        let a = ring[id];
+                        let b = ring[(id + 1) % ring.len()];
+                        // Edges starting or ending in the bridge ends are touching it, not crossing it
+                        if [a, b]
+                            .iter()
+                            .any(|p| p.distance(from) <= Self::EPS || p.distance(to) <= Self::EPS)
+                        {
+                            return false;
+                        }
+                        !Geometry::intersect_segments(from, to, a, b).is_empty()
+                    })
+                })
+        };
+
+        let mut candidates: Vec<usize> = (0..outline.len()).collect();
+        candidates.sort_by(|a, b| {
+            outline[*a]
+                .distance(hole_point)
+                .total_cmp(&outline[*b].distance(hole_point))
+        });
+        let outline_end = candidates
+            .iter()
+            .copied()
+            .find(|id| !crosses_any_edge(hole_point, outline[*id]))
+            .unwrap_or(candidates[0]);
+
+        let mut bridged = Vec::with_capacity(outline.len() + hole.len() + 2);
+        bridged.extend_from_slice(&outline[..=outline_end]);
+        bridged.extend((0..=hole.len()).map(|i| hole[(hole_start + i) % hole.len()]));
+        bridged.extend_from_slice(&outline[outline_end..]);
+        *outline = bridged;
+    }
+
+    fn clip_ears(mut ring: Vec<Pos2>) -> Vec<[Pos2; 3]> {
+        let mut triangles = Vec::with_capacity(ring.len().saturating_sub(2));
+        while ring.len() > 3 {
+            let n = ring.len();
+            let turn = |id: usize| {
+                let previous = ring[(id + n - 1) % n];
+                let next = ring[(id + 1) % n];
+                Geometry::cross(ring[id] - previous, next - ring[id])
+            };
+
+            let ear = (0..n).find(|id| turn(*id) > Self::EPS && Self::is_ear(&ring, *id));
+            // No ear can only be found in degenerate (e.g. self-intersecting) rings,
+            // the most convex vertex is cut off then, so that the algorithm always ends
+            let id = match ear {
+                Some(id) => id,
+                None => (0..n).max_by(|a, b| turn(*a).total_cmp(&turn(*b))).unwrap(),
+            };
+            let triangle = [ring[(id + n - 1) % n], ring[id], ring[(id + 1) % n]];
+            if Geometry::signed_area(&triangle) > Self::EPS {
+                triangles.push(triangle);
+            }
+            ring.remove(id);
+        }
+        if ring.len() == 3 && Geometry::signed_area(&ring) > Self::EPS {
+            triangles.push([ring[0], ring[1], ring[2]]);
+        }
+        triangles
+    }
+
+    fn is_ear(ring: &[Pos2], id: usize) -> bool {
+        let n = ring.len();
+        let a = ring[(id + n - 1) % n];
+        let b = ring[id];
+        let c = ring[(id + 1) % n];
+        ring.iter().all(|p| {
+            // Duplicated vertices (ends of bridges) don't block the ear
+            if [a, b, c].iter().any(|q| q.distance(*p) <= Self::EPS) {
+                return true;
+            }
+            !Self::triangle_contains(a, b, c, *p)
+        })
+    }
+
+    fn triangle_contains(a: Pos2, b: Pos2, c: Pos2, p: Pos2) -> bool {
+        Geometry::cross(b - a, p - a) >= 0.0
+            && Geometry::cross(c - b, p - b) >= 0.0
+            && Geometry::cross(a - c, p - c) >= 0.0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::point::fixtures::points as ring;
+
+    fn area(triangles: &[[Pos2; 3]]) -> f32 {
+        triangles
+            .iter()
+            .map(|triangle| Geometry::signed_area(triangle).abs())
+            .sum()
+    }
+
+    #[test]
+    fn concave_polygon_is_covered_exactly() {
+        // L-shaped polygon has one reflex vertex
+        let shape = Shape::new(
+            ring(&[
+                (0.0, 0.0),
+                (100.0, 0.0),
+                (100.0, 50.0),
+                (50.0, 50.0),
+                (50.0, 100.0),
+                (0.0, 100.0),
+            ]),
+            vec![],
+        );
+        let triangles = Triangulation::triangulate(&shape, 0.5);
+        assert_eq!(triangles.len(), 4);
+        assert!((area(&triangles) - 7500.0).abs() < 0.01);
+    }
+
+    #[test]
+    fn holes_are_left_uncovered() {
+        let shape = Shape::new(
+            ring(&[(0.0, 0.0), (100.0, 0.0), (100.0, 100.0), (0.0, 100.0)]),
+            vec![ring(&[
+                (25.0, 25.0),
+                (75.0, 25.0),
+                (75.0, 75.0),
+                (25.0, 75.0),
+            ])],
+        );
+        let triangles = Triangulation::triangulate(&shape, 0.5);
+        assert_eq!(triangles.len(), 8);
+        assert!((area(&triangles) - 7500.0).abs() < 0.01);
+    }
+
+    #[test]
+    fn degenerate_polygon_gives_no_triangles() {
+        let shape = Shape::new(ring(&[(0.0, 0.0), (50.0, 0.0), (100.0, 0.0)]), vec![]);
+        assert!(Triangulation::triangulate(&shape, 0.5).is_empty());
+    }
+}
@@ -0,0 +1,363 @@
+use egui::{Event, InputState, Key, ModifierNames, Modifiers, PointerButton};
+use serde::{Deserialize, Serialize};
+
+/// Mode of the editor in which actions work, triggers have to be unique only within one mode
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Mode {
+    Editing,
+    Drawing,
+}
+
+impl Mode {
+    pub const ALL: [Mode; 2] = [Mode::Editing, Mode::Drawing];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Mode::Editing => "Editing",
+            Mode::Drawing => "Drawing",
+        }
+    }
+}
+
+/// Everything that can be triggered with a key or a mouse button
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Action {
+    DragPoint,
+    DragPolygon,
+    RemovePoint,
+    SelectRun,
+    OpenContextMenu,
+    MakeHorizontal,
+    MakeVertical,
+    MakeConstantWidth,
+    ApplyG0,
+    ApplyC1,
+    ApplyG1,
+    DrawNewPolygon,
+    DrawFreehand,
+    ShowShortcuts,
+    Undo,
+    FocusNext,
+    FocusPrevious,
+    SwitchFocus,
+    OpenFocusedMenu,
+    CloseMenu,
+    Copy,
+    Paste,
+    Duplicate,
+    NudgeLeft,
+    NudgeRight,
+    NudgeUp,
+    NudgeDown,
+    PullOutG1Handles,
+    RemoveLastPoint,
+    CancelDrawing,
+    CloseDrawnPolygon,
+}
+
+impl Action {
+    pub const ALL: [Action; 31] = [
+        Action::DragPoint,
+        Action::DragPolygon,
+        Action::RemovePoint,
+        Action::SelectRun,
+        Action::OpenContextMenu,
+        Action::MakeHorizontal,
+        Action::MakeVertical,
+        Action::MakeConstantWidth,
+        Action::ApplyG0,
+        Action::ApplyC1,
+        Action::ApplyG1,
+        Action::DrawNewPolygon,
+        Action::DrawFreehand,
+        Action::ShowShortcuts,
+        Action::Undo,
+        Action::FocusNext,
+        Action::FocusPrevious,
+        Action::SwitchFocus,
+        Action::OpenFocusedMenu,
+        Action::CloseMenu,
+        Action::Copy,
+        Action::Paste,
+        Action::Duplicate,
+        Action::NudgeLeft,
+        Action::NudgeRight,
+        Action::NudgeUp,
+        Action::NudgeDown,
+        Action::PullOutG1Handles,
+        Action::RemoveLastPoint,
+        Action::CancelDrawing,
+        Action::CloseDrawnPolygon,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Action::DragPoint => "Drag point",
+            Action::DragPolygon => "Drag whole polygon",
+            Action::RemovePoint => "Remove point",
+            Action::SelectRun => "Select run of vertices",
+            Action::OpenContextMenu => "Open menu of edge or point",
+            Action::MakeHorizontal => "Make selected edge horizontal",
+            Action::MakeVertical => "Make selected edge vertical",
+            Action::MakeConstantWidth => "Make selected edge constant width",
+            Action::ApplyG0 => "Apply G0 in selected point",
+            Action::ApplyC1 => "Apply C1 in selected point",
+            Action::ApplyG1 => "Apply G1 in selected point",
+            Action::DrawNewPolygon => "Draw new polygon",
+            Action::DrawFreehand => "Draw freehand",
+            Action::ShowShortcuts => "Show shortcuts",
+            Action::Undo => "Undo",
+            Action::FocusNext => "Focus next vertex or edge",
+            Action::FocusPrevious => "Focus previous vertex or edge",
+            Action::SwitchFocus => "Switch between vertex and edge focus",
+            Action::OpenFocusedMenu => "Open menu of focused vertex or edge, finish inserting",
+            Action::CloseMenu => "Close menu and clear focus, finish inserting",
+            Action::Copy => "Copy selected run or polygon",
+            Action::Paste => "Paste shapes",
+            Action::Duplicate => "Duplicate selected run or polygon",
+            Action::NudgeLeft => "Move point left (further with Shift)",
+            Action::NudgeRight => "Move point right (further with Shift)",
+            Action::NudgeUp => "Move point up (further with Shift)",
+            Action::NudgeDown => "Move point down (further with Shift)",
+            Action::PullOutG1Handles => "Pull out handles keeping G1",
+            Action::RemoveLastPoint => "Remove last placed point",
+            Action::CancelDrawing => "Cancel drawing",
+            Action::CloseDrawnPolygon => "Close drawn polygon",
+        }
+    }
+
+    pub fn mode(&self) -> Mode {
+        match self {
+            Action::PullOutG1Handles
+            | Action::RemoveLastPoint
+            | Action::CancelDrawing
+            | Action::CloseDrawnPolygon => Mode::Drawing,
+            _ => Mode::Editing,
+        }
+    }
+
+    /// Gestures performed with the mouse on points or edges, they can be bound only to mouse buttons
+    pub fn is_mouse_gesture(&self) -> bool {
+        matches!(
+            self,
+            Action::DragPoint
+                | Action::DragPolygon
+                | Action::RemovePoint
+                | Action::SelectRun
+                | Action::OpenContextMenu
+                | Action::PullOutG1Handles
+        )
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Trigger {
+    Key(Modifiers, Key),
+    Mouse(Modifiers, PointerButton),
+}
+
+impl Trigger {
+    /// Returns trigger pressed in the event, `modifiers` are the ones held during it
+    pub fn from_event(event: &Event, modifiers: Modifiers) -> Option<Trigger> {
+        match event {
+            Event::Key {
+                key,
+                pressed: true,
+                modifiers,
+                ..
+            } => Some(Trigger::Key(*modifiers, *key)),
+            Event::PointerButton {
+                button,
+                pressed: true,
+                modifiers,
+                ..
+            } => Some(Trigger::Mouse(*modifiers, *button)),
+            _ => Self::clipboard_key(event).map(|key| Trigger::Key(modifiers, key)),
+        }
+    }
+
+    /// Keys of clipboard shortcuts, which come as clipboard events instead of key presses
+    fn clipboard_key(event: &Event) -> Option<Key> {
+        match event {
+            Event::Copy => Some(Key::C),
+            Event::Cut => Some(Key::X),
+            Event::Paste(_) => Some(Key::V),
+            _ => None,
+        }
+    }
+
+    pub fn name(&self) -> String {
+        let (modifiers, name) = match self {
+            Trigger::Key(modifiers, key) => (modifiers, key.name()),
+            Trigger::Mouse(modifiers, button) => (
+                modifiers,
+                match button {
+                    PointerButton::Primary => "LMB",
+                    PointerButton::Secondary => "RMB",
+                    PointerButton::Middle => "MMB",
+                    PointerButton::Extra1 => "Mouse 4",
+                    PointerButton::Extra2 => "Mouse 5",
+                },
+            ),
+        };
+        let modifiers = ModifierNames::NAMES.format(modifiers, false);
+        if modifiers.is_empty() {
+            name.to_string()
+        } else {
+            format!("{} + {}", modifiers, name)
+        }
+    }
+}
+
+/// Map from actions to keys and mouse buttons triggering them, which can be changed by user.
+/// It's stored as a list of pairs, actions missing in it keep their default triggers.
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(from = "Vec<(Action, Trigger)>", into = "Vec<(Action, Trigger)>")]
+pub struct Bindings {
+    triggers: Vec<(Action, Trigger)>,
+}
+
+impl Default for Bindings {
+    fn default() -> Self {
+        let mouse = Trigger::Mouse;
+        let key = |key| Trigger::Key(Modifiers::NONE, key);
+        Self {
+            triggers: vec![
+                (
+                    Action::DragPoint,
+                    mouse(Modifiers::NONE, PointerButton::Primary),
+                ),
+                (
+                    Action::DragPolygon,
+                    mouse(Modifiers::CTRL, PointerButton::Primary),
+                ),
+                (
+                    Action::RemovePoint,
+                    mouse(Modifiers::ALT, PointerButton::Primary),
+                ),
+                (
+                    Action::SelectRun,
+                    mouse(Modifiers::SHIFT, PointerButton::Primary),
+                ),
+                (
+                    Action::OpenContextMenu,
+                    mouse(Modifiers::NONE, PointerButton::Secondary),
+                ),
+                (Action::MakeHorizontal, key(Key::H)),
+                (Action::MakeVertical, key(Key::V)),
+                (Action::MakeConstantWidth, key(Key::W)),
+                (Action::ApplyG0, key(Key::Num0)),
+                (Action::ApplyC1, key(Key::Num1)),
+                (Action::ApplyG1, key(Key::G)),
+                (Action::DrawNewPolygon, key(Key::N)),
+                (Action::DrawFreehand, key(Key::F)),
+                (Action::ShowShortcuts, key(Key::F1)),
+                (Action::Undo, Trigger::Key(Modifiers::COMMAND, Key::Z)),
+                (Action::FocusNext, key(Key::Tab)),
+                (
+                    Action::FocusPrevious,
+                    Trigger::Key(Modifiers::SHIFT, Key::Tab),
+                ),
+                (Action::SwitchFocus, key(Key::E)),
+                (Action::OpenFocusedMenu, key(Key::Enter)),
+                (Action::CloseMenu, key(Key::Escape)),
+                (Action::Copy, Trigger::Key(Modifiers::COMMAND, Key::C)),
+                (Action::Paste, Trigger::Key(Modifiers::COMMAND, Key::V)),
+                (Action::Duplicate, Trigger::Key(Modifiers::COMMAND, Key::D)),
+                (Action::NudgeLeft, key(Key::ArrowLeft)),
+                (Action::NudgeRight, key(Key::ArrowRight)),
+                (Action::NudgeUp, key(Key::ArrowUp)),
+                (Action::NudgeDown, key(Key::ArrowDown)),
+                (
+                    Action::PullOutG1Handles,
+                    mouse(Modifiers::ALT, PointerButton::Primary),
+                ),
+                (Action::RemoveLastPoint, key(Key::Backspace)),
+                (Action::CancelDrawing, key(Key::Escape)),
+                (Action::CloseDrawnPolygon, key(Key::Enter)),
+            ],
+        }
+    }
+}
+
+impl From<Vec<(Action, Trigger)>> for Bindings {
+    fn from(triggers: Vec<(Action, Trigger)>) -> Self {
+        let mut bindings = Self::default();
+        for (action, trigger) in triggers {
+            bindings.bind(action, trigger);
+        }
+        bindings
+    }
+}
+
+impl From<Bindings> for Vec<(Action, Trigger)> {
+    fn from(bindings: Bindings) -> Self {
+        bindings.triggers
+    }
+}
+
+impl Bindings {
+    pub fn trigger(&self, action: Action) -> Trigger {
+        self.triggers
+            .iter()
+            .find(|(a, _)| *a == action)
+            .map(|(_, trigger)| *trigger)
+            .expect("Every action has a trigger")
+    }
+
+    /// Binds the trigger to the action. Action of the same mode that used this trigger before gets
+    /// the previous trigger of the rebound one, so that no trigger is shared. That action is returned.
+    pub fn bind(&mut self, action: Action, trigger: Trigger) -> Option<Action> {
+        let previous = self.trigger(action);
+        let swapped = self
+            .triggers
+            .iter_mut()
+            .find(|(a, t)| *a != action && a.mode() == action.mode() && *t == trigger)
+            .map(|(a, t)| {
+                *t = previous;
+                *a
+            });
+        for (a, t) in self.triggers.iter_mut() {
+            if *a == action {
+                *t = trigger;
+            }
+        }
+        swapped
+    }
+
+    /// Whether the key or mouse button of the action was pressed in this frame, with its modifiers held
+    pub fn pressed(&self, input: &InputState, action: Action) -> bool {
+        self.pressed_with(input, action, Modifiers::NONE)
+    }
+
+    /// Whether the key or mouse button of the action was pressed in this frame, with its modifiers
+    /// and the extra ones held
+    pub fn pressed_with(&self, input: &InputState, action: Action, extra: Modifiers) -> bool {
+        match self.trigger(action) {
+            Trigger::Key(modifiers, key) => {
+                input.modifiers.matches_exact(modifiers | extra)
+                    && (input.key_pressed(key)
+                        || input
+                            .events
+                            .iter()
+                            .any(|event| Trigger::clipboard_key(event) == Some(key)))
+            }
+            Trigger::Mouse(modifiers, button) => {
+                input.modifiers.matches_exact(modifiers | extra)
+                    && input.pointer.button_pressed(button)
+            }
+        }
+    }
+
+    /// Whether the key or mouse button of the action is held, together with its modifiers
+    pub fn down(&self, input: &InputState, action: Action) -> bool {
+        match self.trigger(action) {
+            Trigger::Key(modifiers, key) => {
+                input.modifiers.matches_exact(modifiers) && input.key_down(key)
+            }
+            Trigger::Mouse(modifiers, button) => {
+                input.modifiers.matches_exact(modifiers) && input.pointer.button_down(button)
+            }
+        }
+    }
+}
@@ -1,15 +1,23 @@
 use egui::{Color32, Pos2};
 
-use crate::point::{ContinuityType, EdgeConstraint, Point};
+use polygon_model::{
+    bezier::BezierData,
+    point::{ContinuityType, EdgeConstraint, Point},
+    raster::Raster,
+    segment::EdgeSegment,
+};
 
 const POINT_WIDTH: f32 = 4.0;
 const BEZIER_POINT_COLOR: Color32 = Color32::from_rgb(252, 15, 192);
+const INTERSECTION_COLOR: Color32 = Color32::RED;
+const INTERSECTION_MARKER_RADIUS: f32 = 7.0;
 
 pub struct Drawer;
 
 impl Drawer {
     pub fn draw_points(
         points: &[Point],
+        closed: bool,
         selected_point: Option<usize>,
         painter: &egui::Painter,
         color: Color32,
@@ -31,7 +39,10 @@ impl Drawer {
                     width: POINT_WIDTH,
                 },
             );
-            if Point::is_part_of_bezier_segment(points, id) {
+            // Ends of an open path have no continuity
+            if Point::is_part_of_curve_segment(points, closed, id)
+                && !Point::is_endpoint(points, closed, id)
+            {
                 Self::draw_point_info(point, painter);
             }
             #[cfg(feature = "show_debug_info")]
@@ -47,27 +58,159 @@ impl Drawer {
         }
     }
 
+    /// Draws polyline connecting samples of the stroke
+    pub fn draw_stroke(stroke: &[Pos2], painter: &egui::Painter, color: Color32) {
+        const WIDTH: f32 = 1.0;
+        for w in stroke.windows(2) {
+            Self::draw_line_bresenham(painter, color, &w[0], &w[1], WIDTH);
+        }
+    }
+
+    /// Draws closed spline given by its cubic pieces, together with dashed polygon of its control points
+    pub fn draw_spline(
+        cubics: &[[Pos2; 4]],
+        points: &[Point],
+        painter: &egui::Painter,
+        color: Color32,
+        tolerance: f32,
+    ) {
+        const WIDTH: f32 = 1.0;
+        for id in 0..points.len() {
+            let id_next = Point::get_next_index(points, id);
+            Self::draw_dashed_line_bresenham(
+                painter,
+                Color32::GRAY,
+                *points[id].pos(),
+                *points[id_next].pos(),
+                WIDTH,
+            );
+        }
+        for control_points in cubics {
+            let curve_points: Vec<Pos2> = std::iter::once(control_points[0])
+                .chain(BezierData::flatten_control_polygon(
+                    control_points,
+                    tolerance,
+                ))
+                .chain(std::iter::once(control_points[3]))
+                .collect();
+            for w in curve_points.windows(2) {
+                Self::draw_line_bresenham(painter, color, &w[0], &w[1], WIDTH);
+            }
+        }
+    }
+
+    pub fn draw_intersections(intersections: &[Pos2], painter: &egui::Painter) {
+        for intersection in intersections {
+            painter.circle_stroke(
+                *intersection,
+                INTERSECTION_MARKER_RADIUS,
+                egui::Stroke {
+                    color: INTERSECTION_COLOR,
+                    width: 2.0,
+                },
+            );
+        }
+    }
+
+    /// Draws lines of the grid points are snapped to, covering the whole rect
+    pub fn draw_grid(rect: egui::Rect, step: f32, painter: &egui::Painter, color: Color32) {
+        let stroke = egui::Stroke { color, width: 1.0 };
+        let mut x = (rect.left() / step).ceil() * step;
+        while x <= rect.right() {
+            painter.vline(x, rect.y_range(), stroke);
+            x += step;
+        }
+        let mut y = (rect.top() / step).ceil() * step;
+        while y <= rect.bottom() {
+            painter.hline(rect.x_range(), y, stroke);
+            y += step;
+        }
+    }
+
+    /// Marks the point moved with arrow keys, or vertex or edge focused with keyboard
+    pub fn draw_square_marker(pos: Pos2, painter: &egui::Painter, color: Color32) {
+        painter.rect_stroke(
+            egui::Rect::from_center_size(pos, egui::Vec2::splat(4.0 * POINT_WIDTH)),
+            0.0,
+            egui::Stroke { color, width: 1.0 },
+        );
+    }
+
+    pub fn draw_triangles(triangles: &[[Pos2; 3]], painter: &egui::Painter, color: Color32) {
+        const WIDTH: f32 = 1.0;
+        for triangle in triangles {
+            for id in 0..triangle.len() {
+                let id_next = (id + 1) % triangle.len();
+                Self::draw_line_bresenham(painter, color, &triangle[id], &triangle[id_next], WIDTH);
+            }
+        }
+    }
+
+    pub fn draw_convex_hull(hull: &[Pos2], painter: &egui::Painter, color: Color32) {
+        const WIDTH: f32 = 1.0;
+        for id in 0..hull.len() {
+            let id_next = (id + 1) % hull.len();
+            Self::draw_dashed_line_bresenham(painter, color, hull[id], hull[id_next], WIDTH);
+        }
+    }
+
+    /// Draws teeth of the curvature comb and the envelope connecting their ends
+    pub fn draw_curvature_comb(combs: &[Vec<[Pos2; 2]>], painter: &egui::Painter, color: Color32) {
+        const WIDTH: f32 = 1.0;
+        for teeth in combs {
+            for [point, tooth_end] in teeth {
+                Self::draw_line_bresenham(painter, color, point, tooth_end, WIDTH);
+            }
+            for envelope in teeth.windows(2) {
+                Self::draw_line_bresenham(painter, color, &envelope[0][1], &envelope[1][1], WIDTH);
+            }
+        }
+    }
+
+    /// Writes difference of curvatures next to each joint of curves
+    pub fn draw_curvature_jumps(jumps: &[(Pos2, f32)], painter: &egui::Painter, color: Color32) {
+        for (pos, jump) in jumps {
+            painter.text(
+                *pos + egui::Vec2::new(10.0, -10.0),
+                egui::Align2::LEFT_BOTTOM,
+                format!("Δκ {:.4}", jump),
+                egui::FontId::monospace(12.0),
+                color,
+            );
+        }
+    }
+
+    #[allow(clippy::too_many_arguments)]
     pub fn draw_polygon_builtin(
         points: &[Point],
+        closed: bool,
         selected_point: Option<usize>,
         selected_edge_start_index: Option<usize>,
         painter: &egui::Painter,
         color: Color32,
         special_color: Color32,
         width: f32,
+        tolerance: f32,
     ) {
-        for id in 0..points.len() {
+        for id in 0..Point::edges_count(points, closed) {
             let current_color = if id == selected_edge_start_index.unwrap_or(usize::MAX)
                 || (id == selected_point.unwrap_or(usize::MAX)
-                    && points[id].is_start_of_bezier_segment())
+                    && points[id].is_start_of_curve_segment())
             {
                 special_color
             } else {
                 color
             };
             let id_next = Point::get_next_index(points, id);
-            if points[id].is_start_of_bezier_segment() {
-                Self::draw_brezier_segment(&points[id], &points[id_next], painter, current_color);
+            if points[id].is_start_of_curve_segment() {
+                Self::draw_curve_segment(
+                    &points[id],
+                    &points[id_next],
+                    painter,
+                    current_color,
+                    tolerance,
+                );
+                Self::draw_edge_info(points, id, painter);
             } else {
                 painter.line_segment(
                     [*points[id].pos(), *points[id_next].pos()],
@@ -86,42 +229,55 @@ impl Drawer {
         painter: &egui::Painter,
         color: Color32,
         width: f32,
+        tolerance: f32,
     ) {
         if points.is_empty() {
             return;
         }
         for id in 0..points.len() - 1 {
-            painter.line_segment(
-                [
-                    *points[id].pos(),
-                    *points[Point::get_next_index(points, id)].pos(),
-                ],
-                egui::Stroke { color, width },
-            );
+            let id_next = Point::get_next_index(points, id);
+            if points[id].is_start_of_curve_segment() {
+                Self::draw_curve_segment(&points[id], &points[id_next], painter, color, tolerance);
+            } else {
+                painter.line_segment(
+                    [*points[id].pos(), *points[id_next].pos()],
+                    egui::Stroke { color, width },
+                );
+            }
         }
     }
 
+    #[allow(clippy::too_many_arguments)]
     pub fn draw_polygon_bresenham(
         points: &[Point],
+        closed: bool,
         selected_point: Option<usize>,
         selected_edge_start_index: Option<usize>,
         painter: &egui::Painter,
         color: Color32,
         special_color: Color32,
+        tolerance: f32,
     ) {
         const WIDTH: f32 = 1.0;
-        for id in 0..points.len() {
+        for id in 0..Point::edges_count(points, closed) {
             let current_color = if id == selected_edge_start_index.unwrap_or(usize::MAX)
                 || (id == selected_point.unwrap_or(usize::MAX)
-                    && points[id].is_start_of_bezier_segment())
+                    && points[id].is_start_of_curve_segment())
             {
                 special_color
             } else {
                 color
             };
             let id_next = Point::get_next_index(points, id);
-            if points[id].is_start_of_bezier_segment() {
-                Self::draw_brezier_segment(&points[id], &points[id_next], painter, current_color);
+            if points[id].is_start_of_curve_segment() {
+                Self::draw_curve_segment(
+                    &points[id],
+                    &points[id_next],
+                    painter,
+                    current_color,
+                    tolerance,
+                );
+                Self::draw_edge_info(points, id, painter);
             } else {
                 Self::draw_line_bresenham(
                     painter,
@@ -139,26 +295,130 @@ impl Drawer {
         points: &[Point],
         painter: &egui::Painter,
         color: Color32,
+        tolerance: f32,
     ) {
         const WIDTH: f32 = 1.0;
         if points.is_empty() {
             return;
         }
         for id in 0..points.len() - 1 {
-            Self::draw_line_bresenham(
-                painter,
-                color,
-                points[id].pos(),
-                points[Point::get_next_index(points, id)].pos(),
-                WIDTH,
-            );
+            let id_next = Point::get_next_index(points, id);
+            if points[id].is_start_of_curve_segment() {
+                Self::draw_curve_segment(&points[id], &points[id_next], painter, color, tolerance);
+            } else {
+                Self::draw_line_bresenham(
+                    painter,
+                    color,
+                    points[id].pos(),
+                    points[id_next].pos(),
+                    WIDTH,
+                );
+            }
             Self::draw_edge_info(points, id, painter);
         }
     }
 
+    /// Marks points of the run going from `start` to `end`
+    pub fn draw_selected_run(
+        points: &[Point],
+        start: usize,
+        end: usize,
+        painter: &egui::Painter,
+        color: Color32,
+    ) {
+        let mut id = start;
+        loop {
+            painter.circle_stroke(
+                *points[id].pos(),
+                2.0 * POINT_WIDTH,
+                egui::Stroke { color, width: 2.0 },
+            );
+            if id == end {
+                break;
+            }
+            id = Point::get_next_index(points, id);
+        }
+    }
+
+    /// Draws dashed line from the last placed point to the cursor, together with its length and angle
+    pub fn draw_rubber_band(start: Pos2, end: Pos2, painter: &egui::Painter, color: Color32) {
+        Self::draw_dashed_line_bresenham(painter, color, start, end, 1.0);
+        let offset = end - start;
+        // Angle is measured counterclockwise as seen on the screen, where y grows downwards
+        let angle = (-offset.y).atan2(offset.x).to_degrees();
+        painter.text(
+            end + egui::vec2(12.0, 12.0),
+            egui::Align2::LEFT_TOP,
+            format!("{:.1}  {:.1}°", offset.length(), angle),
+            egui::FontId::monospace(14.0),
+            color,
+        );
+    }
+
+    /// Draws tangent handles pulled out of the point
+    pub fn draw_handles(point: Pos2, handles: &[Pos2], painter: &egui::Painter) {
+        for handle in handles {
+            Self::draw_dashed_line_bresenham(painter, Color32::GRAY, point, *handle, 1.0);
+            painter.circle(
+                *handle,
+                POINT_WIDTH,
+                BEZIER_POINT_COLOR,
+                egui::Stroke {
+                    color: BEZIER_POINT_COLOR,
+                    width: POINT_WIDTH,
+                },
+            );
+        }
+    }
+
+    /// Draws only the outline of the polygon, without control points and constraints info
+    pub fn draw_outline_builtin(
+        points: &[Point],
+        closed: bool,
+        painter: &egui::Painter,
+        color: Color32,
+        width: f32,
+        tolerance: f32,
+    ) {
+        for id in 0..Point::edges_count(points, closed) {
+            let id_next = Point::get_next_index(points, id);
+            let outline_points =
+                points[id]
+                    .segment()
+                    .get_curve_points(&points[id], &points[id_next], tolerance);
+            for w in outline_points.windows(2) {
+                painter.line_segment([w[0], w[1]], egui::Stroke { color, width });
+            }
+        }
+    }
+
+    /// Draws only the outline of the polygon, without control points and constraints info
+    pub fn draw_outline_bresenham(
+        points: &[Point],
+        closed: bool,
+        painter: &egui::Painter,
+        color: Color32,
+        tolerance: f32,
+    ) {
+        const WIDTH: f32 = 1.0;
+        for id in 0..Point::edges_count(points, closed) {
+            let id_next = Point::get_next_index(points, id);
+            let outline_points =
+                points[id]
+                    .segment()
+                    .get_curve_points(&points[id], &points[id_next], tolerance);
+            for w in outline_points.windows(2) {
+                Self::draw_line_bresenham(painter, color, &w[0], &w[1], WIDTH);
+            }
+        }
+    }
+
     fn draw_edge_info(points: &[Point], id: usize, painter: &egui::Painter) {
         let id_next = Point::get_next_index(points, id);
-        let mut pos = Point::get_middle_point(&points[id], &points[id_next]);
+        // Middle of the edge measured along it, so that labels of curves lie on them
+        let mut pos = points[id]
+            .segment()
+            .evenly_spaced_points(&points[id], &points[id_next], 3)[1];
 
         let text = match points[id].constraint() {
             Some(c) => match c {
@@ -168,6 +428,7 @@ impl Drawer {
                     "V"
                 }
                 EdgeConstraint::ConstWidth(width) => &format!("C({})", width),
+                EdgeConstraint::ConstLength(length) => &format!("L({})", length),
             },
             None => "",
         };
@@ -184,7 +445,7 @@ impl Drawer {
         {
             pos.x -= 10.0;
             pos.y -= 10.0;
-            let width = points[id].pos().distance(*points[id_next].pos());
+            let width = points[id].segment().length(&points[id], &points[id_next]);
             painter.text(
                 pos,
                 egui::Align2::LEFT_TOP,
@@ -200,6 +461,8 @@ impl Drawer {
             ContinuityType::G0 => "G0",
             ContinuityType::C1 => "C1",
             ContinuityType::G1 => "G1",
+            ContinuityType::C2 => "C2",
+            ContinuityType::G2 => "G2",
         };
 
         let pos = *point.pos() + egui::Vec2::new(10.0, 10.0);
@@ -220,71 +483,17 @@ impl Drawer {
         end: &Pos2,
         width: f32,
     ) {
-        let x1 = start.x as i32;
-        let y1 = start.y as i32;
-        let x2 = end.x as i32;
-        let y2 = end.y as i32;
-
-        let dx = x2 - x1;
-        let dy = y2 - y1;
-
-        let abs_dx = dx.abs();
-        let abs_dy = dy.abs();
-
-        let mut x = x1;
-        let mut y = y1;
-
-        Self::paint_pixel(
-            painter,
-            Pos2 {
-                x: x as f32,
-                y: y as f32,
-            },
-            width,
-            color,
-        );
-
-        if abs_dx > abs_dy {
-            let mut d = 2 * abs_dy - abs_dx;
-            for _ in 0..abs_dx {
-                x = if dx < 0 { x - 1 } else { x + 1 };
-                if d < 0 {
-                    d += 2 * abs_dy
-                } else {
-                    y = if dy < 0 { y - 1 } else { y + 1 };
-                    d += 2 * abs_dy - 2 * abs_dx;
-                }
-                Self::paint_pixel(
-                    painter,
-                    Pos2 {
-                        x: x as f32,
-                        y: y as f32,
-                    },
-                    width,
-                    color,
-                );
-            }
-        } else {
-            let mut d = 2 * abs_dx - abs_dy;
-            for _ in 0..abs_dy {
-                y = if dy < 0 { y - 1 } else { y + 1 };
-                if d < 0 {
-                    d += 2 * abs_dx
-                } else {
-                    x = if dx < 0 { x - 1 } else { x + 1 };
-                    d += 2 * abs_dx - 2 * abs_dy;
-                }
-                Self::paint_pixel(
-                    painter,
-                    Pos2 {
-                        x: x as f32,
-                        y: y as f32,
-                    },
-                    width,
-                    color,
-                );
-            }
-        }
+        Raster::line(*start, *end, |x, y| {
+            Self::paint_pixel(
+                painter,
+                Pos2 {
+                    x: x as f32,
+                    y: y as f32,
+                },
+                width,
+                color,
+            )
+        });
     }
 
     fn draw_dashed_line_bresenham(
@@ -372,14 +581,18 @@ impl Drawer {
         }
     }
 
-    fn draw_brezier_segment(start: &Point, end: &Point, painter: &egui::Painter, color: Color32) {
-        let bezier_data = start
-            .bezier_data()
-            .expect("draw_bezier_segment should only be call for point with bezier data");
-        let inner_points = bezier_data.inner_points();
-        for inner_point in inner_points {
+    fn draw_curve_segment(
+        start: &Point,
+        end: &Point,
+        painter: &egui::Painter,
+        color: Color32,
+        tolerance: f32,
+    ) {
+        let segment = start.segment();
+        let control_points = segment.control_points(start, end);
+        for control_point in &control_points {
             painter.circle(
-                *inner_point,
+                *control_point,
                 POINT_WIDTH,
                 BEZIER_POINT_COLOR,
                 egui::Stroke {
@@ -388,19 +601,26 @@ impl Drawer {
                 },
             );
         }
-        let all_points = [*start.pos(), inner_points[0], inner_points[1], *end.pos()];
-        for id in 0..all_points.len() - 1 {
+        // Arc is controlled by its middle point, so it has no control polygon
+        let control_polygon: Vec<Pos2> = match segment {
+            EdgeSegment::CircularArc(_) => vec![*start.pos(), *end.pos()],
+            _ => std::iter::once(*start.pos())
+                .chain(control_points)
+                .chain(std::iter::once(*end.pos()))
+                .collect(),
+        };
+        for id in 0..control_polygon.len() - 1 {
             let id_next = id + 1;
             Self::draw_dashed_line_bresenham(
                 painter,
                 Color32::GRAY,
-                all_points[id],
-                all_points[id_next],
+                control_polygon[id],
+                control_polygon[id_next],
                 1.0,
             );
         }
-        let curve_points = bezier_data.get_bezier_curve_points(start, end);
-        for id in 0..(curve_points.len() - 1) {
+        let curve_points = segment.get_curve_points(start, end, tolerance);
+        for id in 0..curve_points.len().saturating_sub(1) {
             let next_id = id + 1;
             Self::draw_line_bresenham(
                 painter,
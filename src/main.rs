@@ -1,18 +1,16 @@
-mod bezier;
+mod actions;
 mod drawer;
-mod point;
 mod polygon_editor;
 mod popups;
 
 use polygon_editor::PolygonEditor;
 
 fn main() {
-    let app = PolygonEditor::default();
     let native_options = eframe::NativeOptions::default();
     let res = eframe::run_native(
         "Polygon Editor",
         native_options,
-        Box::new(|_cc| Ok(Box::new(app))),
+        Box::new(|cc| Ok(Box::new(PolygonEditor::new(cc)))),
     );
 
     if let Err(e) = res {
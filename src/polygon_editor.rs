@@ -1,9 +1,29 @@
-use egui::{Color32, Pos2, Rounding, Vec2, Window};
+use std::path::Path;
 
-use crate::{
+use egui::{Color32, Pos2, Rect, Rounding, Vec2, Window};
+
+use polygon_model::{
     bezier::BezierData,
-    drawer::Drawer,
+    clipping::{BooleanOperation, Clipper},
+    corners::Corners,
+    curvature::CurvatureComb,
+    document::Document,
+    fitting::CurveFitting,
+    geometry::Geometry,
+    hull::ConvexHull,
+    intersections::SelfIntersections,
+    offset::{JoinStyle, PolygonOffset},
     point::{ContinuityType, EdgeConstraint, Point},
+    segment::EdgeSegment,
+    shape::Shape,
+    simplification::Simplification,
+    spline::{Spline, SplineKind},
+    triangulation::Triangulation,
+};
+
+use crate::{
+    actions::{Action, Bindings, Mode, Trigger},
+    drawer::Drawer,
     popups::Popups,
 };
 
@@ -13,9 +33,27 @@ enum LineDrawingAlgorithm {
     Bresenham,
 }
 
+/// Vertex or edge chosen with keyboard, whose context menu can be opened without the mouse
+#[derive(Clone, Copy, PartialEq)]
+enum KeyboardFocus {
+    Vertex(usize),
+    /// Id of the first vertex of the edge
+    Edge(usize),
+}
+
+/// Point moved with arrow keys
+#[derive(Clone, Copy, PartialEq)]
+enum NudgeTarget {
+    Vertex(usize),
+    /// (point id, id of control point of the curve starting in that point)
+    ControlPoint(usize, usize),
+}
+
 #[derive(PartialEq)]
 enum PolygonMode {
     Drawing,
+    /// Polygon is drawn with a single stroke, which is then fitted with bezier segments
+    Freehand,
     Editing,
 }
 
@@ -26,18 +64,107 @@ pub struct PolygonEditor {
     /// List of all polygon points
     /// At the same time, each point is the start of the edge and the next one is the end of it
     points: Vec<Point>,
+    /// Whether the last point is connected with the first one, otherwise the polygon is an open path
+    closed: bool,
+    /// Holes inside the edited polygon, they can't be edited directly
+    holes: Vec<Vec<Point>>,
+    /// All other shapes, that are not edited at the moment
+    shapes: Vec<Shape>,
+    /// Operands of boolean operation, 0 is the edited polygon and i > 0 is shapes[i - 1]
+    boolean_operation_operands: (usize, usize),
+    boolean_operation: BooleanOperation,
+    /// Distance of the offset outline, negative values create inset
+    offset_distance: f32,
+    offset_join_style: JoinStyle,
+    /// Maximal ratio of miter length to the offset distance, longer miters are replaced with bevels
+    offset_miter_limit: f32,
+    /// Maximal distance of removed vertices from the simplified outline
+    simplify_tolerance: f32,
+    /// Whether to draw the polygon simplified with current tolerance
+    show_simplify_preview: bool,
+    /// Radius of the last rounded corner
+    fillet_radius: f32,
+    /// Length of the edge that replaced the last cut off corner
+    chamfer_length: f32,
+    /// Largest allowed distance between curves and polylines used to draw, fill and export them
+    flattening_tolerance: f32,
+    /// Tension of cardinal spline used to smooth the polygon
+    smoothing_tension: f32,
+    /// Outgoing tangent handle of the last point in drawing mode, pulled out by dragging from it
+    pen_handle: Option<Pos2>,
+    /// Incoming tangent handle of the first point in drawing mode, used when the polygon is closed
+    pen_first_handle: Option<Pos2>,
+    /// Whether LMB is still held after placing the last point in drawing mode
+    pen_dragging: bool,
+    /// All shapes (the edited one first) from before drawing started, restored when drawing is cancelled
+    shapes_before_drawing: Option<Vec<Shape>>,
+    /// Samples of the stroke currently drawn in freehand mode
+    freehand_stroke: Vec<Pos2>,
+    /// Largest allowed distance between the freehand stroke and curves fitted to it
+    freehand_tolerance: f32,
+    /// When set, vertices of the edited polygon describe closed spline of this kind instead of its edges
+    spline_kind: Option<SplineKind>,
+    /// Whether to draw curvature comb of the curves together with curvature jumps in their joints
+    show_curvature_comb: bool,
+    /// Length of comb teeth per unit of curvature
+    curvature_comb_scale: f32,
     /// Id of point inside points that is currently being dragged by user
     dragged_index: Option<usize>,
-    /// Bezier control point that is currenlty dragged: (point id, id of control point in that point bezier data)
-    bezier_control_point_dragged: Option<(usize, usize)>,
+    /// Control point of a curve that is currenlty dragged: (point id, id of control point of the curve starting in that point)
+    control_point_dragged: Option<(usize, usize)>,
     /// Id of point inside points that is currently used to dragg whole polygon
     polygon_dragged_index: Option<usize>,
     /// Id of edge (meaning id of the first vertex of it) currently selected for context menu
     selected_edge_start_index: Option<usize>,
     /// Id of point currently selected for context menu
     selected_point_index: Option<usize>,
+    /// Vertex or control point grabbed with LMB or selected with RMB last, it's moved with arrow keys
+    nudge_target: Option<NudgeTarget>,
+    /// Vertex or edge reached with Tab, its menu is opened with a key
+    keyboard_focus: Option<KeyboardFocus>,
+    /// Whether the first button of the context menu should take keyboard focus, set when the menu is opened with a key
+    focus_menu: bool,
+    /// All shapes (the edited one first) from before each of the last edits, the latest edit last
+    undo_history: Vec<Vec<Shape>>,
+    /// Whether the latest undo step was made by nudging, following nudges are merged into it then
+    nudging: bool,
+    /// Whether dragged and nudged points land on nodes of the grid
+    snap_to_grid: bool,
+    /// Distance between lines of the grid
+    grid_step: f32,
+    /// First and last vertex of the run selected with shift + LMB, which is copied instead of the whole polygon
+    selected_run: Option<(usize, usize)>,
+    /// JSON document copied last, pasted when paste is bound to other key than the system one
+    copied_json: Option<String>,
+    /// Id of the last point of the chain inserted with LMB between chosen vertex and its successor,
+    /// set while inserting vertices
+    insert_after_index: Option<usize>,
     /// Data related to all popups
     popups: Popups,
+    /// Points in which edges of the polygon cross each other, recalculated every frame
+    self_intersections: Vec<Pos2>,
+    /// Whether dragging that would make simple polygon self-intersecting should be refused
+    prevent_self_intersections: bool,
+    /// Convex hull of the edited polygon, recalculated every frame and used to skip hit testing far from it
+    convex_hull: Vec<Pos2>,
+    /// Whether to draw convex hull of the edited polygon
+    show_convex_hull: bool,
+    /// Whether to draw triangulation of the edited polygon
+    show_triangulation: bool,
+    /// Triangles covering the edited polygon, recalculated every frame when they are shown
+    triangles: Vec<[Pos2; 3]>,
+    /// Path of the file the document is saved to and loaded from
+    document_path: String,
+    /// Whether triangulation of the edited polygon should be saved together with the document
+    export_triangulation: bool,
+    /// Result of the last action that user should be informed about
+    status_message: String,
+    /// Keys and mouse buttons triggering actions
+    bindings: Bindings,
+    /// Action waiting for the next pressed key or mouse button, which becomes its new trigger
+    rebound_action: Option<Action>,
+    /// Whether to show window with shortcuts
+    show_shortcuts_window: bool,
     /// Whether to show window with tutorial
     show_tutorial_window: bool,
     /// Whether to show window with implementation
@@ -46,6 +173,28 @@ pub struct PolygonEditor {
 
 impl PolygonEditor {
     const CONTEXT_MENU_MIN_WDITH: f32 = 150.0;
+    /// Largest distance from which points and edges can be grabbed
+    const HIT_TEST_MARGIN: f32 = 10.0;
+    /// Edges drawn with Shift held have angles that are multiples of this
+    const ANGLE_SNAP_STEP: f32 = std::f32::consts::FRAC_PI_4;
+    /// Distance points are moved by arrow keys, and by arrow keys with Shift held
+    const NUDGE_STEP: f32 = 1.0;
+    const NUDGE_STEP_LARGE: f32 = 10.0;
+    /// Oldest undo steps are forgotten above this count
+    const MAX_UNDO_STEPS: usize = 100;
+    /// Key under which bindings chosen by user are kept in eframe storage
+    const BINDINGS_STORAGE_KEY: &'static str = "bindings";
+
+    /// Creates the editor with bindings stored when the app was closed last time
+    pub fn new(cc: &eframe::CreationContext<'_>) -> Self {
+        Self {
+            bindings: cc
+                .storage
+                .and_then(|storage| eframe::get_value(storage, Self::BINDINGS_STORAGE_KEY))
+                .unwrap_or_default(),
+            ..Self::default()
+        }
+    }
 
     pub fn new_with_drawing_mode() -> Self {
         Self {
@@ -55,76 +204,477 @@ impl PolygonEditor {
         }
     }
 
+    pub fn new_with_freehand_mode() -> Self {
+        Self {
+            polygon_mode: PolygonMode::Freehand,
+            points: vec![],
+            ..Default::default()
+        }
+    }
+
     pub fn handle_dragging_points(&mut self, ctx: &egui::Context) {
         let mouse_pos = ctx.pointer_interact_pos();
         if let Some(pos) = mouse_pos {
             // Check user is holding LMB
-            if ctx.input(|i| i.pointer.button_down(egui::PointerButton::Primary)) {
+            if ctx.input(|i| self.bindings.down(i, Action::DragPoint)) {
+                // Keep the state from before the move, so that it can be restored if the move is refused.
+                // Intersections found earlier in this frame tell whether that state is simple.
+                let points_before_move = (self.prevent_self_intersections
+                    && self.self_intersections.is_empty()
+                    && (self.dragged_index.is_some() || self.control_point_dragged.is_some()))
+                .then(|| self.points.clone());
+                let snapped_pos = if self.snap_to_grid {
+                    Geometry::snap_to_grid(pos, self.grid_step)
+                } else {
+                    pos
+                };
                 // If already dragging then move point
                 if let Some(index) = self.dragged_index {
-                    Point::update_position(&mut self.points, index, pos);
-                } else if let Some((point_index, inner_point_index)) =
-                    self.bezier_control_point_dragged
-                {
-                    match self.points[point_index].bezier_data_mut() {
-                        Some(bd) => {
-                            bd.update_inner_point_position(inner_point_index, pos);
-                            Point::update_position_after_control_point_moved(
-                                &mut self.points,
-                                point_index,
-                                inner_point_index,
-                            )
-                        }
-
-                        None => eprintln!(
-                            "Trying to move bezier control point for point without bezier segment"
-                        ),
-                    }
-                } else {
+                    Point::update_position(&mut self.points, self.closed, index, snapped_pos);
+                } else if let Some((point_index, inner_point_index)) = self.control_point_dragged {
+                    self.move_control_point(point_index, inner_point_index, snapped_pos);
+                } else if self.is_near_polygon(pos) {
                     for (i, point) in self.points.iter().enumerate() {
                         // Start dragging the point if it's close enough
                         if (*point.pos() - pos).length() < 10.0 {
                             self.dragged_index = Some(i);
+                            self.nudge_target = Some(NudgeTarget::Vertex(i));
                             break;
                         }
-                        if let Some(bezier_data) = point.bezier_data() {
-                            for (ip, inner_point) in bezier_data.inner_points().iter().enumerate() {
-                                if (*inner_point - pos).length() < 10.0 {
-                                    self.bezier_control_point_dragged = Some((i, ip));
-                                    break;
-                                }
+                        let next_point = &self.points[Point::get_next_index(&self.points, i)];
+                        let control_points = point.segment().control_points(point, next_point);
+                        for (ip, control_point) in control_points.iter().enumerate() {
+                            if (*control_point - pos).length() < 10.0 {
+                                self.control_point_dragged = Some((i, ip));
+                                self.nudge_target = Some(NudgeTarget::ControlPoint(i, ip));
+                                break;
                             }
                         }
                     }
+                    if self.dragged_index.is_some() || self.control_point_dragged.is_some() {
+                        self.push_undo_step();
+                    }
+                }
+                if let Some(points_before_move) = points_before_move {
+                    self.refuse_self_intersecting_move(points_before_move);
                 }
             } else {
                 // Stop dragging if LMB no longer hold
                 self.dragged_index = None;
-                self.bezier_control_point_dragged = None;
+                self.control_point_dragged = None;
+            }
+        }
+    }
+
+    /// Moves control point of the curve starting in the point and keeps continuity in both its ends
+    fn move_control_point(&mut self, point_index: usize, inner_point_index: usize, pos: Pos2) {
+        if self.points[point_index].is_start_of_curve_segment() {
+            let next_index = Point::get_next_index(&self.points, point_index);
+            let (start, end) = (self.points[point_index], self.points[next_index]);
+            self.points[point_index].segment_mut().update_control_point(
+                &start,
+                &end,
+                inner_point_index,
+                pos,
+            );
+            Point::update_position_after_control_point_moved(
+                &mut self.points,
+                self.closed,
+                point_index,
+                inner_point_index,
+            )
+        } else {
+            eprintln!("Trying to move control point for point without curve segment");
+        }
+    }
+
+    /// Position of the nudged point, None if it no longer exists
+    fn nudge_target_pos(&self, target: NudgeTarget) -> Option<Pos2> {
+        match target {
+            NudgeTarget::Vertex(id) => self.points.get(id).map(|point| *point.pos()),
+            NudgeTarget::ControlPoint(id, inner_id) => {
+                let point = self.points.get(id)?;
+                let next_point = &self.points[Point::get_next_index(&self.points, id)];
+                point
+                    .segment()
+                    .control_points(point, next_point)
+                    .get(inner_id)
+                    .copied()
+            }
+        }
+    }
+
+    /// Arrow keys move the nudge target by 1px, by 10px with Shift held and by grid step when snapping
+    /// to grid. Constraints and continuity are kept the same way as when dragging.
+    pub fn handle_nudging(&mut self, ctx: &egui::Context) {
+        // Any click ends the series of nudges merged into one undo step
+        if ctx.input(|i| i.pointer.any_pressed()) {
+            self.nudging = false;
+        }
+        // Arrows move keyboard focus between buttons of the open menu instead
+        let menu_has_focus = (self.selected_edge_start_index.is_some()
+            || self.selected_point_index.is_some())
+            && ctx.memory(|memory| memory.focused().is_some());
+        if ctx.wants_keyboard_input() || menu_has_focus {
+            return;
+        }
+        let Some(target) = self.nudge_target else {
+            return;
+        };
+        let (direction, large_step) = ctx.input(|i| {
+            [
+                (Action::NudgeLeft, -Vec2::X),
+                (Action::NudgeRight, Vec2::X),
+                (Action::NudgeUp, -Vec2::Y),
+                (Action::NudgeDown, Vec2::Y),
+            ]
+            .into_iter()
+            .fold(
+                (Vec2::ZERO, false),
+                |(sum, large_step), (action, direction)| {
+                    let large = self
+                        .bindings
+                        .pressed_with(i, action, egui::Modifiers::SHIFT);
+                    if large || self.bindings.pressed(i, action) {
+                        (sum + direction, large_step || large)
+                    } else {
+                        (sum, large_step)
+                    }
+                },
+            )
+        });
+        if direction == Vec2::ZERO {
+            return;
+        }
+        let Some(pos) = self.nudge_target_pos(target) else {
+            self.nudge_target = None;
+            return;
+        };
+        let new_pos = if self.snap_to_grid {
+            Geometry::step_on_grid(pos, direction, self.grid_step)
+        } else if large_step {
+            pos + direction * Self::NUDGE_STEP_LARGE
+        } else {
+            pos + direction * Self::NUDGE_STEP
+        };
+        if !self.nudging {
+            self.push_undo_step();
+            self.nudging = true;
+        }
+        let points_before_move = (self.prevent_self_intersections
+            && self.self_intersections.is_empty())
+        .then(|| self.points.clone());
+        match target {
+            NudgeTarget::Vertex(id) => {
+                Point::update_position(&mut self.points, self.closed, id, new_pos)
+            }
+            NudgeTarget::ControlPoint(id, inner_id) => {
+                self.move_control_point(id, inner_id, new_pos)
+            }
+        }
+        if let Some(points_before_move) = points_before_move {
+            if self.refuse_self_intersecting_move(points_before_move) {
+                self.status_message = "Move refused, polygon would intersect itself".to_string();
+            }
+        }
+    }
+
+    /// Restores the simple polygon from before the move if the move made it self-intersecting.
+    /// Returns whether the move was refused.
+    fn refuse_self_intersecting_move(&mut self, points_before_move: Vec<Point>) -> bool {
+        if SelfIntersections::is_simple(&self.points, self.closed) {
+            return false;
+        }
+        self.points = points_before_move;
+        true
+    }
+
+    /// Remembers current shapes, so that the edit that follows can be undone
+    fn push_undo_step(&mut self) {
+        self.push_undo_state(self.all_shapes());
+    }
+
+    /// Remembers shapes from before an edit, so that it can be undone
+    fn push_undo_state(&mut self, shapes: Vec<Shape>) {
+        self.nudging = false;
+        if self.undo_history.len() == Self::MAX_UNDO_STEPS {
+            self.undo_history.remove(0);
+        }
+        self.undo_history.push(shapes);
+    }
+
+    /// Goes back to shapes from before the latest edit
+    fn undo(&mut self) {
+        self.nudging = false;
+        match self.undo_history.pop() {
+            Some(shapes) => {
+                self.restore_shapes(shapes);
+                self.status_message = "Undone".to_string();
             }
+            None => self.status_message = "Nothing to undo".to_string(),
         }
     }
 
+    /// Makes the first shape the edited polygon and the others ordinary shapes, then continues editing
+    fn restore_shapes(&mut self, mut shapes: Vec<Shape>) {
+        let edited = shapes.remove(0);
+        self.set_edited_shape(edited);
+        self.shapes = shapes;
+        self.shapes_before_drawing = None;
+        self.polygon_mode = PolygonMode::Editing;
+        self.reset_selection();
+    }
+
     pub fn handle_adding_point_in_drawing_mode(
         &mut self,
         ctx: &egui::Context,
         main_panel_width: f32,
     ) {
         let mouse_pos = ctx.pointer_interact_pos();
+        if ctx.input(|i| i.pointer.button_released(egui::PointerButton::Primary)) {
+            self.pen_dragging = false;
+        }
         if let Some(pos) = mouse_pos {
+            if self.pen_dragging
+                && ctx.input(|i| i.pointer.button_down(egui::PointerButton::Primary))
+            {
+                let keep_handle_length =
+                    ctx.input(|i| self.bindings.down(i, Action::PullOutG1Handles));
+                self.pull_out_pen_handles(pos, keep_handle_length);
+                return;
+            }
             // If clicking outside the panel (on controls panel) ignore this click
             if pos.x > main_panel_width {
                 return;
             }
-            if ctx.input(|i| i.pointer.button_clicked(egui::PointerButton::Primary)) {
+            if ctx.input(|i| i.pointer.button_pressed(egui::PointerButton::Primary)) {
                 if self.points.len() >= 3 && (*self.points[0].pos() - pos).length() < 10.0 {
-                    self.polygon_mode = PolygonMode::Editing;
+                    self.close_drawn_polygon();
+                } else if self.points.len() >= 2
+                    && (*self.points[self.points.len() - 1].pos() - pos).length() < 10.0
+                {
+                    self.finish_drawn_path();
                 }
                 // It means that we didnt change the mode, so user wants to add new point
                 if self.polygon_mode == PolygonMode::Drawing {
-                    self.points.push(Point::new(pos));
+                    let snap = ctx.input(|i| i.modifiers.shift);
+                    let pos = self.next_drawn_position(pos, snap);
+                    let mut point = Point::new(pos);
+                    // Point stays a corner unless handles are pulled out of it
+                    point.apply_G0();
+                    if let (Some(handle), Some(previous)) =
+                        (self.pen_handle, self.points.last_mut())
+                    {
+                        let previous_pos = *previous.pos();
+                        previous.init_bezier_data([handle, pos + (previous_pos - pos) / 3.0]);
+                    }
+                    self.points.push(point);
+                    self.pen_handle = None;
+                    self.pen_dragging = true;
+                }
+            }
+        }
+    }
+
+    /// Returns position in which the next point will be placed, with Shift the edge leading to it
+    /// is snapped to multiples of 45 degrees
+    fn next_drawn_position(&self, pos: Pos2, snap: bool) -> Pos2 {
+        match self.points.last() {
+            Some(last) if snap => Geometry::snap_to_angle(*last.pos(), pos, Self::ANGLE_SNAP_STEP),
+            _ => pos,
+        }
+    }
+
+    pub fn handle_keys_in_drawing_mode(&mut self, ctx: &egui::Context) {
+        // Keys typed into text fields are not meant for drawing
+        if ctx.wants_keyboard_input() {
+            return;
+        }
+        let (remove, cancel, close) = ctx.input(|i| {
+            (
+                self.bindings.pressed(i, Action::RemoveLastPoint),
+                self.bindings.pressed(i, Action::CancelDrawing),
+                self.bindings.pressed(i, Action::CloseDrawnPolygon),
+            )
+        });
+        if remove {
+            self.remove_last_drawn_point();
+        }
+        if cancel {
+            self.cancel_drawing();
+        } else if close {
+            if self.points.len() >= 3 {
+                self.close_drawn_polygon();
+            } else {
+                self.status_message = "Polygon needs at least 3 points".to_string();
+            }
+        }
+    }
+
+    /// Takes back the last placed point, handle pulled out of the previous point is restored
+    fn remove_last_drawn_point(&mut self) {
+        if self.points.pop().is_none() {
+            return;
+        }
+        self.pen_dragging = false;
+        self.pen_handle = None;
+        match self.points.last_mut() {
+            Some(last) => {
+                // Corners (G0) have no handles, their curves come only from handles of the next point
+                if let EdgeSegment::CubicBezier(bd) = last.segment() {
+                    if *last.continuity_type() != ContinuityType::G0 {
+                        self.pen_handle = Some(bd.inner_points()[0]);
+                    }
+                }
+                last.remove_curve();
+            }
+            None => self.pen_first_handle = None,
+        }
+    }
+
+    /// Throws away the drawn points and goes back to shapes from before drawing started
+    fn cancel_drawing(&mut self) {
+        self.points.clear();
+        self.pen_handle = None;
+        self.pen_first_handle = None;
+        self.pen_dragging = false;
+        match self.shapes_before_drawing.take() {
+            Some(shapes) if !shapes.is_empty() => {
+                self.restore_shapes(shapes);
+                self.status_message = "Drawing cancelled".to_string();
+            }
+            _ => self.status_message = "There is no previous polygon to go back to".to_string(),
+        }
+    }
+
+    /// Sets tangent handles of the last drawn point, so that the outgoing one is at `pos`.
+    /// Incoming one is mirrored (C1), or only points in the opposite direction
+    /// and keeps the third of the incoming edge length (G1).
+    fn pull_out_pen_handles(&mut self, pos: Pos2, keep_handle_length: bool) {
+        // Distance of the cursor from the point below which dragging is treated as a click
+        const DRAG_THRESHOLD: f32 = 3.0;
+        let Some(last_index) = self.points.len().checked_sub(1) else {
+            return;
+        };
+        let last_pos = *self.points[last_index].pos();
+        if last_pos.distance(pos) < DRAG_THRESHOLD {
+            return;
+        }
+        let incoming_handle = if keep_handle_length && last_index > 0 {
+            let incoming_length = self.points[last_index - 1].pos().distance(last_pos) / 3.0;
+            last_pos - (pos - last_pos).normalized() * incoming_length
+        } else {
+            last_pos + (last_pos - pos)
+        };
+        if keep_handle_length {
+            self.points[last_index].apply_G1();
+        } else {
+            self.points[last_index].apply_C1();
+        }
+        self.pen_handle = Some(pos);
+
+        if last_index == 0 {
+            self.pen_first_handle = Some(incoming_handle);
+            return;
+        }
+        // Edges in drawing mode are either lines or cubic bezier segments
+        let previous = &mut self.points[last_index - 1];
+        match previous.segment_mut() {
+            EdgeSegment::CubicBezier(bd) => bd.update_inner_point_position(1, incoming_handle),
+            _ => {
+                let previous_pos = *previous.pos();
+                previous.init_bezier_data([
+                    previous_pos + (last_pos - previous_pos) / 3.0,
+                    incoming_handle,
+                ]);
+            }
+        }
+    }
+
+    /// Finishes drawing, the closing edge becomes a bezier segment if any of its ends has a handle
+    fn close_drawn_polygon(&mut self) {
+        let first_pos = *self.points[0].pos();
+        let last = self.points.last_mut().unwrap();
+        let last_pos = *last.pos();
+        if self.pen_handle.is_some() || self.pen_first_handle.is_some() {
+            last.init_bezier_data([
+                self.pen_handle
+                    .unwrap_or(last_pos + (first_pos - last_pos) / 3.0),
+                self.pen_first_handle
+                    .unwrap_or(first_pos + (last_pos - first_pos) / 3.0),
+            ]);
+        }
+        self.pen_handle = None;
+        self.pen_first_handle = None;
+        self.pen_dragging = false;
+        self.closed = true;
+        self.record_drawing();
+        self.polygon_mode = PolygonMode::Editing;
+    }
+
+    /// Lets the finished drawing be undone, when there were any shapes before it started
+    fn record_drawing(&mut self) {
+        if let Some(shapes) = self.shapes_before_drawing.take() {
+            self.push_undo_state(shapes);
+        }
+    }
+
+    /// Finishes drawing without connecting the last point with the first one
+    fn finish_drawn_path(&mut self) {
+        self.pen_handle = None;
+        self.pen_first_handle = None;
+        self.pen_dragging = false;
+        self.closed = false;
+        self.record_drawing();
+        self.polygon_mode = PolygonMode::Editing;
+    }
+
+    pub fn handle_drawing_freehand_stroke(&mut self, ctx: &egui::Context, main_panel_width: f32) {
+        // Minimal distance between consecutive samples of the stroke
+        const SAMPLES_DISTANCE: f32 = 2.0;
+        if !ctx.wants_keyboard_input()
+            && ctx.input(|i| self.bindings.pressed(i, Action::CancelDrawing))
+        {
+            self.freehand_stroke.clear();
+            self.cancel_drawing();
+            return;
+        }
+        let mouse_pos = ctx.pointer_interact_pos();
+        if ctx.input(|i| i.pointer.button_down(egui::PointerButton::Primary)) {
+            if let Some(pos) = mouse_pos {
+                // Strokes can't start on the controls panel
+                if self.freehand_stroke.is_empty() && pos.x > main_panel_width {
+                    return;
+                }
+                if self
+                    .freehand_stroke
+                    .last()
+                    .is_none_or(|last| last.distance(pos) >= SAMPLES_DISTANCE)
+                {
+                    self.freehand_stroke.push(pos);
                 }
             }
+        } else if !self.freehand_stroke.is_empty() {
+            self.finish_freehand_stroke();
+        }
+    }
+
+    /// Fits the drawn stroke with a closed polygon, which can be undone back to shapes from before drawing
+    fn finish_freehand_stroke(&mut self) {
+        let stroke = std::mem::take(&mut self.freehand_stroke);
+        match CurveFitting::fit_closed(&stroke, self.freehand_tolerance) {
+            Some(points) => {
+                self.status_message = format!(
+                    "Stroke with {} samples fitted with {} bezier segments",
+                    stroke.len(),
+                    points.len()
+                );
+                self.points = points;
+                self.closed = true;
+                self.record_drawing();
+                self.polygon_mode = PolygonMode::Editing;
+            }
+            None => self.status_message = "Stroke is too short to make a polygon".to_string(),
         }
     }
 
@@ -134,21 +684,25 @@ impl PolygonEditor {
         let mouse_pos = ctx.pointer_interact_pos();
         if let Some(pos) = mouse_pos {
             // Check if user is holding ctrl + LMB
-            if ctx
-                .input(|i| i.pointer.button_down(egui::PointerButton::Primary) && i.modifiers.ctrl)
-            {
+            if ctx.input(|i| self.bindings.down(i, Action::DragPolygon)) {
                 // If already dragging then move all points
                 if let Some(index) = self.polygon_dragged_index {
                     let previous_pos = self.points[index];
                     let diff = pos - *previous_pos.pos();
                     Point::update_position_all(&mut self.points, diff);
-                } else {
+                    for hole in self.holes.iter_mut() {
+                        Point::update_position_all(hole, diff);
+                    }
+                } else if self.is_near_polygon(pos) {
                     for (i, point) in self.points.iter().enumerate() {
                         // Start dragging the point if it's close enough
                         if (*point.pos() - pos).length() < 10.0 {
                             self.polygon_dragged_index = Some(i);
                         }
                     }
+                    if self.polygon_dragged_index.is_some() {
+                        self.push_undo_step();
+                    }
                 }
             } else {
                 self.polygon_dragged_index = None;
@@ -159,19 +713,24 @@ impl PolygonEditor {
     pub fn handle_selecting_edge_or_point(&mut self, ctx: &egui::Context) {
         let mouse_pos = ctx.pointer_hover_pos();
         if let Some(pos) = mouse_pos {
-            if ctx.input(|i| i.pointer.button_down(egui::PointerButton::Secondary)) {
+            if ctx.input(|i| self.bindings.down(i, Action::OpenContextMenu)) {
                 let mut edge_selected_now = false;
                 let mut point_selected_now = false;
-                for id in 0..self.points.len() {
-                    if self.points[id].pos().distance(pos) < 10.0
-                        && Point::is_part_of_bezier_segment(&self.points, id)
-                    {
+                let ids = if self.is_near_polygon(pos) {
+                    0..self.points.len()
+                } else {
+                    0..0
+                };
+                for id in ids {
+                    if self.points[id].pos().distance(pos) < 10.0 {
                         self.selected_point_index = Some(id);
+                        self.nudge_target = Some(NudgeTarget::Vertex(id));
                         point_selected_now = true;
                         break;
                     }
-                    if Point::contains_point(&self.points, id, &pos)
-                        && !self.points[id].is_start_of_bezier_segment()
+                    // Last point of an open path starts no edge
+                    if id < Point::edges_count(&self.points, self.closed)
+                        && Point::contains_point(&self.points, id, &pos)
                     {
                         self.selected_edge_start_index = Some(id);
                         edge_selected_now = true;
@@ -188,13 +747,44 @@ impl PolygonEditor {
         }
     }
 
+    /// First shift + LMB click on a point starts the run, the second one ends it
+    pub fn handle_selecting_run(&mut self, ctx: &egui::Context) {
+        let Some(pos) = ctx.pointer_interact_pos() else {
+            return;
+        };
+        if !ctx.input(|i| self.bindings.pressed(i, Action::SelectRun)) || !self.is_near_polygon(pos)
+        {
+            return;
+        }
+        let Some(id) = self
+            .points
+            .iter()
+            .position(|point| point.pos().distance(pos) < Self::HIT_TEST_MARGIN)
+        else {
+            self.selected_run = None;
+            return;
+        };
+        self.selected_run = match self.selected_run {
+            Some((start, end)) if start == end && start != id => {
+                // Open path can't go around, so its runs always go from the lower index
+                if self.closed {
+                    Some((start, id))
+                } else {
+                    Some((start.min(id), start.max(id)))
+                }
+            }
+            _ => Some((id, id)),
+        };
+    }
+
     pub fn handle_removing_point(&mut self, ctx: &egui::Context) {
-        if self.points.len() <= 3 {
+        if self.points.len() <= self.min_points_count() {
             return;
         }
         let mouse_pos = ctx.pointer_hover_pos();
         if let Some(pos) = mouse_pos {
-            if ctx.input(|i| i.pointer.button_down(egui::PointerButton::Primary) && i.modifiers.alt)
+            if ctx.input(|i| self.bindings.down(i, Action::RemovePoint))
+                && self.is_near_polygon(pos)
             {
                 let mut id: Option<usize> = None;
                 for (i, point) in self.points.iter().enumerate() {
@@ -203,7 +793,10 @@ impl PolygonEditor {
                     }
                 }
                 if let Some(id) = id {
-                    Point::remove_at(&mut self.points, id);
+                    self.push_undo_step();
+                    Point::remove_at(&mut self.points, self.closed, id);
+                    self.selected_run = None;
+                    self.nudge_target = None;
                 }
             }
         }
@@ -212,9 +805,13 @@ impl PolygonEditor {
     pub fn show_context_menu_for_selected_edge(&mut self, ctx: &egui::Context, ui: &egui::Ui) {
         if let Some(selected_id) = self.selected_edge_start_index {
             let can_add_constraint_or_bezier_segment = !self.points[selected_id].has_constraint()
-                && !self.points[selected_id].is_start_of_bezier_segment();
+                && !self.points[selected_id].is_start_of_curve_segment();
+            let can_add_length_constraint = !self.points[selected_id].has_constraint()
+                && self.points[selected_id].is_start_of_curve_segment();
             let number_of_buttons = if can_add_constraint_or_bezier_segment {
-                5
+                7
+            } else if can_add_length_constraint {
+                3
             } else {
                 2
             };
@@ -239,15 +836,18 @@ impl PolygonEditor {
                             ui.with_layout(
                                 egui::Layout::top_down_justified(egui::Align::LEFT),
                                 |ui| {
-                                    if ui
-                                        .add(egui::Button::new("Add midpoint").rounding(Rounding {
+                                    let add_midpoint_button = ui.add(
+                                        egui::Button::new("Add midpoint").rounding(Rounding {
                                             sw: 0.0,
                                             se: 0.0,
                                             ..Default::default()
-                                        }))
-                                        .clicked()
-                                    {
+                                        }),
+                                    );
+                                    self.take_menu_focus(&add_midpoint_button);
+                                    if add_midpoint_button.clicked() {
+                                        self.push_undo_step();
                                         Point::add_on_edge(&mut self.points, selected_id);
+                                        self.selected_run = None;
                                         self.selected_edge_start_index = None;
                                     }
                                     if can_add_constraint_or_bezier_segment {
@@ -259,6 +859,7 @@ impl PolygonEditor {
                                             )
                                             .clicked()
                                         {
+                                            self.push_undo_step();
                                             let initial_points =
                                                 Point::get_points_between_for_initial_bezier(
                                                     &self.points[selected_id],
@@ -272,6 +873,63 @@ impl PolygonEditor {
                                             let same_pos = *self.points[selected_id].pos();
                                             Point::update_position(
                                                 &mut self.points,
+                                                self.closed,
+                                                selected_id,
+                                                same_pos,
+                                            );
+                                            self.selected_edge_start_index = None;
+                                        }
+                                        // Quadratic bezier button
+                                        if ui
+                                            .add(
+                                                egui::Button::new(
+                                                    "Change into quadratic bezier segment",
+                                                )
+                                                .rounding(Rounding::ZERO),
+                                            )
+                                            .clicked()
+                                        {
+                                            self.push_undo_step();
+                                            let [a, b] =
+                                                Point::get_points_between_for_initial_bezier(
+                                                    &self.points[selected_id],
+                                                    &self.points[Point::get_next_index(
+                                                        &self.points,
+                                                        selected_id,
+                                                    )],
+                                                );
+                                            self.points[selected_id]
+                                                .init_quadratic_bezier_data(a.lerp(b, 0.5));
+                                            let same_pos = *self.points[selected_id].pos();
+                                            Point::update_position(
+                                                &mut self.points,
+                                                self.closed,
+                                                selected_id,
+                                                same_pos,
+                                            );
+                                            self.selected_edge_start_index = None;
+                                        }
+                                        // Arc button
+                                        if ui
+                                            .add(
+                                                egui::Button::new("Change into arc")
+                                                    .rounding(Rounding::ZERO),
+                                            )
+                                            .clicked()
+                                        {
+                                            self.push_undo_step();
+                                            let sweep = Point::get_sweep_for_initial_arc(
+                                                &self.points[selected_id],
+                                                &self.points[Point::get_next_index(
+                                                    &self.points,
+                                                    selected_id,
+                                                )],
+                                            );
+                                            self.points[selected_id].init_arc_data(sweep);
+                                            let same_pos = *self.points[selected_id].pos();
+                                            Point::update_position(
+                                                &mut self.points,
+                                                self.closed,
                                                 selected_id,
                                                 same_pos,
                                             );
@@ -289,14 +947,10 @@ impl PolygonEditor {
                                             )
                                             .clicked()
                                         {
-                                            self.points[selected_id].apply_horizontal_constraint();
-                                            let same_pos = *self.points[selected_id].pos();
-                                            Point::update_position(
-                                                &mut self.points,
+                                            self.constrain_edge(
                                                 selected_id,
-                                                same_pos,
+                                                EdgeConstraint::Horizontal,
                                             );
-                                            self.selected_edge_start_index = None;
                                         }
                                         // Vertical button
                                         if ui
@@ -310,14 +964,10 @@ impl PolygonEditor {
                                             )
                                             .clicked()
                                         {
-                                            self.points[selected_id].apply_vertical_constraint();
-                                            let same_pos = *self.points[selected_id].pos();
-                                            Point::update_position(
-                                                &mut self.points,
+                                            self.constrain_edge(
                                                 selected_id,
-                                                same_pos,
+                                                EdgeConstraint::Vertical,
                                             );
-                                            self.selected_edge_start_index = None;
                                         }
                                         // Const width button
                                         let const_width_button = ui.add(
@@ -350,15 +1000,10 @@ impl PolygonEditor {
                                         if self.popups.const_width_constraint_submitted() {
                                             let new_width =
                                                 self.popups.const_width_constraint_user_input();
-                                            self.points[selected_id]
-                                                .apply_width_constraint(new_width);
-                                            let same_pos = *self.points[selected_id].pos();
-                                            Point::update_position(
-                                                &mut self.points,
+                                            self.constrain_edge(
                                                 selected_id,
-                                                same_pos,
+                                                EdgeConstraint::ConstWidth(new_width),
                                             );
-                                            self.selected_edge_start_index = None;
                                             self.popups.reset_const_width_constraint_submitted();
                                         }
                                     } else if self.points[selected_id].has_constraint() {
@@ -372,9 +1017,72 @@ impl PolygonEditor {
                                             ),
                                         );
                                         if response.clicked() {
+                                            self.push_undo_step();
                                             self.points[selected_id].remove_constraint();
                                             self.selected_edge_start_index = None;
                                         }
+                                    } else if can_add_length_constraint {
+                                        let response = ui.add(
+                                            egui::Button::new("Change into line")
+                                                .rounding(Rounding::ZERO),
+                                        );
+                                        if response.clicked() {
+                                            self.push_undo_step();
+                                            self.points[selected_id].remove_curve();
+                                            let same_pos = *self.points[selected_id].pos();
+                                            Point::update_position(
+                                                &mut self.points,
+                                                self.closed,
+                                                selected_id,
+                                                same_pos,
+                                            );
+                                            self.selected_edge_start_index = None;
+                                        }
+                                        // Const length button
+                                        let const_length_button = ui.add(
+                                            egui::Button::new("Make constant length").rounding(
+                                                Rounding {
+                                                    nw: 0.0,
+                                                    ne: 0.0,
+                                                    ..Default::default()
+                                                },
+                                            ),
+                                        );
+                                        self.popups
+                                            .render_const_length_constraint_popup_below_widget(
+                                                ui,
+                                                &const_length_button,
+                                            );
+                                        if const_length_button.clicked() {
+                                            let selected_edge_end_index =
+                                                Point::get_next_index(&self.points, selected_id);
+                                            let length = self.points[selected_id].segment().length(
+                                                &self.points[selected_id],
+                                                &self.points[selected_edge_end_index],
+                                            );
+
+                                            self.popups
+                                                .open_const_length_constraint_popup_below_widget(
+                                                    ui,
+                                                    length.round() as i32,
+                                                );
+                                        }
+                                        if self.popups.const_length_constraint_submitted() {
+                                            let new_length =
+                                                self.popups.const_length_constraint_user_input();
+                                            self.push_undo_step();
+                                            self.points[selected_id]
+                                                .apply_length_constraint(new_length);
+                                            let same_pos = *self.points[selected_id].pos();
+                                            Point::update_position(
+                                                &mut self.points,
+                                                self.closed,
+                                                selected_id,
+                                                same_pos,
+                                            );
+                                            self.selected_edge_start_index = None;
+                                            self.popups.reset_const_length_constraint_submitted();
+                                        }
                                     }
                                 },
                             );
@@ -384,15 +1092,17 @@ impl PolygonEditor {
     }
 
     pub fn show_context_menu_for_selected_point(&mut self, ctx: &egui::Context) {
-        // Only dispaly context menu for point that is either start or end of bezier segment
         if let Some(selected_id) = self.selected_point_index {
-            if !Point::is_part_of_bezier_segment(&self.points, selected_id) {
-                return;
-            }
-
             let container_pos = *self.points[selected_id].pos() + Vec2::new(10.0, 10.0);
-            let display_remove_bezier_button =
-                self.points[selected_id].is_start_of_bezier_segment();
+            // Continuity only makes sense for points that are either start or end of bezier segment
+            let is_endpoint = Point::is_endpoint(&self.points, self.closed, selected_id);
+            let display_continuity_buttons = !is_endpoint
+                && Point::is_part_of_curve_segment(&self.points, self.closed, selected_id);
+            let display_second_order_buttons =
+                Point::joins_cubic_segments(&self.points, selected_id);
+            let display_remove_bezier_button = self.points[selected_id].is_start_of_curve_segment();
+            let can_change_corner =
+                !is_endpoint && Corners::can_change_corner(&self.points, selected_id);
 
             egui::containers::Area::new("edge_context_menu".into())
                 .fixed_pos(container_pos)
@@ -407,70 +1117,180 @@ impl PolygonEditor {
                             ui.with_layout(
                                 egui::Layout::top_down_justified(egui::Align::LEFT),
                                 |ui| {
-                                    // G0 button
-                                    if ui
-                                        .add(egui::Button::new("Apply G0").rounding(Rounding {
-                                            sw: 0.0,
-                                            se: 0.0,
-                                            ..Default::default()
-                                        }))
-                                        .clicked()
-                                    {
-                                        self.points[selected_id].apply_G0();
-                                        let same_pos = *self.points[selected_id].pos();
-                                        Point::update_position(
-                                            &mut self.points,
-                                            selected_id,
-                                            same_pos,
+                                    if display_continuity_buttons {
+                                        // G0 button
+                                        let g0_button = ui.add(
+                                            egui::Button::new("Apply G0").rounding(Rounding {
+                                                sw: 0.0,
+                                                se: 0.0,
+                                                ..Default::default()
+                                            }),
                                         );
-                                        self.selected_point_index = None;
+                                        self.take_menu_focus(&g0_button);
+                                        if g0_button.clicked() {
+                                            self.apply_continuity(selected_id, ContinuityType::G0);
+                                        }
+                                        // G1 button
+                                        if ui
+                                            .add(
+                                                egui::Button::new("Apply G1")
+                                                    .rounding(Rounding::ZERO),
+                                            )
+                                            .clicked()
+                                        {
+                                            self.apply_continuity(selected_id, ContinuityType::G1);
+                                        }
+                                        // C1 button
+                                        if ui
+                                            .add(
+                                                egui::Button::new("Apply C1")
+                                                    .rounding(Rounding::ZERO),
+                                            )
+                                            .clicked()
+                                        {
+                                            self.apply_continuity(selected_id, ContinuityType::C1);
+                                        }
                                     }
-                                    // G1 button
-                                    if ui
-                                        .add(egui::Button::new("Apply G1").rounding(Rounding::ZERO))
-                                        .clicked()
-                                    {
-                                        self.points[selected_id].apply_G1();
+                                    if display_continuity_buttons && display_second_order_buttons {
+                                        // G2 button
+                                        if ui
+                                            .add(
+                                                egui::Button::new("Apply G2")
+                                                    .rounding(Rounding::ZERO),
+                                            )
+                                            .clicked()
+                                        {
+                                            self.apply_continuity(selected_id, ContinuityType::G2);
+                                        }
+                                        // C2 button
+                                        if ui
+                                            .add(
+                                                egui::Button::new("Apply C2")
+                                                    .rounding(Rounding::ZERO),
+                                            )
+                                            .clicked()
+                                        {
+                                            self.apply_continuity(selected_id, ContinuityType::C2);
+                                        }
+                                    }
+                                    // Remove bezier segment button
+                                    let remove_curve_clicked = display_remove_bezier_button && {
+                                        let remove_curve_button = ui.add(
+                                            egui::Button::new(format!(
+                                                "Remove {}",
+                                                self.points[selected_id].segment().name()
+                                            ))
+                                            .rounding(Rounding::ZERO),
+                                        );
+                                        self.take_menu_focus(&remove_curve_button);
+                                        remove_curve_button.clicked()
+                                    };
+                                    if remove_curve_clicked {
+                                        self.push_undo_step();
+                                        self.points[selected_id].remove_curve();
                                         let same_pos = *self.points[selected_id].pos();
                                         Point::update_position(
                                             &mut self.points,
+                                            self.closed,
                                             selected_id,
                                             same_pos,
                                         );
+
                                         self.selected_point_index = None;
                                     }
-                                    // C1 button
-                                    if ui
-                                        .add(egui::Button::new("Apply C1").rounding(Rounding::ZERO))
-                                        .clicked()
-                                    {
-                                        self.points[selected_id].apply_C1();
-                                        let same_pos = *self.points[selected_id].pos();
-                                        Point::update_position(
+                                    // Insert vertices button
+                                    let insert_button = ui.add_enabled(
+                                        selected_id < Point::edges_count(&self.points, self.closed),
+                                        egui::Button::new("Insert vertices after here")
+                                            .rounding(Rounding::ZERO),
+                                    );
+                                    self.take_menu_focus(&insert_button);
+                                    if insert_button.clicked() {
+                                        self.start_inserting_vertices(selected_id);
+                                    }
+                                    // Open or close path button
+                                    let path_button = if self.closed {
+                                        ui.add(
+                                            egui::Button::new("Open path here")
+                                                .rounding(Rounding::ZERO),
+                                        )
+                                    } else {
+                                        ui.add_enabled(
+                                            is_endpoint,
+                                            egui::Button::new("Close path")
+                                                .rounding(Rounding::ZERO),
+                                        )
+                                    };
+                                    self.take_menu_focus(&path_button);
+                                    if path_button.clicked() {
+                                        if self.closed {
+                                            self.open_path_at(selected_id);
+                                        } else {
+                                            self.close_path();
+                                        }
+                                    }
+                                    // Fillet button
+                                    let fillet_button = ui.add_enabled(
+                                        can_change_corner,
+                                        egui::Button::new("Round corner").rounding(Rounding::ZERO),
+                                    );
+                                    self.popups
+                                        .render_fillet_popup_below_widget(ui, &fillet_button);
+                                    if fillet_button.clicked() {
+                                        self.popups
+                                            .open_fillet_popup_below_widget(ui, self.fillet_radius);
+                                    }
+                                    if self.popups.fillet_submitted() {
+                                        self.fillet_radius = self.popups.fillet_radius_user_input();
+                                        let shapes_before_edit = self.all_shapes();
+                                        if Corners::fillet(
                                             &mut self.points,
                                             selected_id,
-                                            same_pos,
-                                        );
+                                            self.fillet_radius,
+                                        ) {
+                                            self.push_undo_state(shapes_before_edit);
+                                        } else {
+                                            self.status_message =
+                                                "Edges are too short for given radius".to_string();
+                                        }
+                                        self.selected_run = None;
                                         self.selected_point_index = None;
+                                        self.popups.reset_fillet_submitted();
                                     }
-                                    // Remove bezier segment button
-                                    if display_remove_bezier_button
-                                        && ui
-                                            .add(
-                                                egui::Button::new("Remove bezier segment")
-                                                    .rounding(Rounding::ZERO),
-                                            )
-                                            .clicked()
-                                    {
-                                        self.points[selected_id].remove_bezier_data();
-                                        let same_pos = *self.points[selected_id].pos();
-                                        Point::update_position(
+                                    // Chamfer button
+                                    let chamfer_button = ui.add_enabled(
+                                        can_change_corner,
+                                        egui::Button::new("Cut off corner").rounding(Rounding {
+                                            nw: 0.0,
+                                            ne: 0.0,
+                                            ..Default::default()
+                                        }),
+                                    );
+                                    self.popups
+                                        .render_chamfer_popup_below_widget(ui, &chamfer_button);
+                                    if chamfer_button.clicked() {
+                                        self.popups.open_chamfer_popup_below_widget(
+                                            ui,
+                                            self.chamfer_length,
+                                        );
+                                    }
+                                    if self.popups.chamfer_submitted() {
+                                        self.chamfer_length =
+                                            self.popups.chamfer_length_user_input();
+                                        let shapes_before_edit = self.all_shapes();
+                                        if Corners::chamfer(
                                             &mut self.points,
                                             selected_id,
-                                            same_pos,
-                                        );
-
+                                            self.chamfer_length,
+                                        ) {
+                                            self.push_undo_state(shapes_before_edit);
+                                        } else {
+                                            self.status_message =
+                                                "Edges are too short for given length".to_string();
+                                        }
+                                        self.selected_run = None;
                                         self.selected_point_index = None;
+                                        self.popups.reset_chamfer_submitted();
                                     }
                                 },
                             );
@@ -479,6 +1299,928 @@ impl PolygonEditor {
         }
     }
 
+    /// Gives keyboard focus to the button, when it's the first enabled one of the menu opened with a key,
+    /// so that Tab, arrows and Enter work within the menu
+    fn take_menu_focus(&mut self, button: &egui::Response) {
+        if self.focus_menu && button.enabled() {
+            button.request_focus();
+            self.focus_menu = false;
+        }
+    }
+
+    /// Applies constraint to the edge, the reason is shown when it can't be applied
+    fn constrain_edge(&mut self, edge_start_index: usize, constraint: EdgeConstraint) {
+        self.selected_edge_start_index = None;
+        let shapes_before_edit = self.all_shapes();
+        match Point::constrain_edge(&mut self.points, self.closed, edge_start_index, constraint) {
+            Ok(()) => self.push_undo_state(shapes_before_edit),
+            Err(e) => self.status_message = e.to_string(),
+        }
+    }
+
+    /// Applies continuity to the point, the reason is shown when it can't be applied
+    fn apply_continuity(&mut self, point_index: usize, continuity: ContinuityType) {
+        self.selected_point_index = None;
+        let shapes_before_edit = self.all_shapes();
+        match Point::apply_continuity(&mut self.points, self.closed, point_index, continuity) {
+            Ok(()) => self.push_undo_state(shapes_before_edit),
+            Err(e) => self.status_message = e.to_string(),
+        }
+    }
+
+    /// Starts inserting new points between the vertex and its successor. The edge between them
+    /// is replaced with a chain of lines, so its constraint and curve are removed.
+    fn start_inserting_vertices(&mut self, point_index: usize) {
+        // Whole insertion is undone at once
+        self.push_undo_step();
+        self.reset_selection();
+        let point = &mut self.points[point_index];
+        let mut removed = vec![];
+        if let Some(constraint) = point.constraint() {
+            removed.push(constraint.name());
+        }
+        if point.is_start_of_curve_segment() {
+            removed.push(point.segment().name());
+        }
+        point.remove_constraint();
+        point.remove_curve();
+        let pos = *point.pos();
+        Point::update_position(&mut self.points, self.closed, point_index, pos);
+        self.insert_after_index = Some(point_index);
+
+        let removed_info = if removed.is_empty() {
+            String::new()
+        } else {
+            format!(" Split edge lost its {}.", removed.join(" and "))
+        };
+        self.status_message = format!(
+            "Click LMB to insert vertices, Enter or Escape to finish.{}",
+            removed_info
+        );
+    }
+
+    pub fn handle_inserting_vertices(&mut self, ctx: &egui::Context, main_panel_width: f32) {
+        let Some(insert_after_index) = self.insert_after_index else {
+            return;
+        };
+        let finish = ctx.input(|i| {
+            self.bindings.pressed(i, Action::OpenFocusedMenu)
+                || self.bindings.pressed(i, Action::CloseMenu)
+        }) && !ctx.wants_keyboard_input();
+        if finish {
+            self.insert_after_index = None;
+            self.status_message = "Finished inserting vertices".to_string();
+            return;
+        }
+        let Some(pos) = ctx.pointer_interact_pos() else {
+            return;
+        };
+        if pos.x <= main_panel_width
+            && ctx.input(|i| i.pointer.button_pressed(egui::PointerButton::Primary))
+        {
+            let new_index = insert_after_index + 1;
+            self.points.insert(new_index, Point::new(pos));
+            Point::update_position(&mut self.points, self.closed, new_index, pos);
+            self.insert_after_index = Some(new_index);
+            self.status_message =
+                "Vertex inserted, click LMB to insert the next one, Enter or Escape to finish"
+                    .to_string();
+        }
+    }
+
+    /// Returns the selected run as an open path, or the whole edited shape when no run is selected
+    fn copied_shape(&self) -> Option<Shape> {
+        match self.selected_run {
+            Some((start, end)) if start != end => {
+                Some(Shape::new_open(Point::copy_run(&self.points, start, end)))
+            }
+            Some(_) => None,
+            None => Some(self.shape(0)),
+        }
+    }
+
+    /// Adds shapes from the document in JSON text, so that the middle of them lies at `pos`
+    fn paste_shapes(&mut self, text: &str, pos: Pos2) {
+        let mut shapes = match Document::from_json(text) {
+            Ok(document) => document.into_shapes(),
+            Err(e) => {
+                self.status_message = format!("Can't paste: {}", e);
+                return;
+            }
+        };
+        if shapes.is_empty() || !shapes.iter().all(Shape::is_valid) {
+            self.status_message = "Can't paste: clipboard contains invalid shapes".to_string();
+            return;
+        }
+        let bounds = Rect::from_points(
+            &shapes
+                .iter()
+                .flat_map(|shape| shape.outline().iter().map(|point| *point.pos()))
+                .collect::<Vec<_>>(),
+        );
+        for shape in shapes.iter_mut() {
+            shape.translate(pos - bounds.center());
+        }
+        self.push_undo_step();
+        self.status_message = format!("Pasted {} shapes", shapes.len());
+        self.shapes.extend(shapes);
+    }
+
+    /// Ctrl + C copies the selected run (or the whole polygon) to the clipboard as JSON document,
+    /// ctrl + V pastes shapes from it at the cursor and ctrl + D duplicates the selection there.
+    /// When paste is bound to other key, clipboard can't be read, so the last copied shapes are pasted.
+    pub fn handle_clipboard(&mut self, ctx: &egui::Context) {
+        // Text fields use the clipboard on their own
+        if ctx.wants_keyboard_input() {
+            return;
+        }
+        // Pasted shapes are placed next to the copied ones when the cursor is outside of the window
+        const PASTE_OFFSET: Vec2 = Vec2::new(20.0, 20.0);
+        let (copy, paste, duplicate) = ctx.input(|i| {
+            let copy = self.bindings.pressed(i, Action::Copy);
+            let paste = self
+                .bindings
+                .pressed(i, Action::Paste)
+                .then(|| {
+                    i.events
+                        .iter()
+                        .find_map(|e| match e {
+                            egui::Event::Paste(text) => Some(text.clone()),
+                            _ => None,
+                        })
+                        .or_else(|| self.copied_json.clone())
+                })
+                .flatten();
+            let duplicate = self.bindings.pressed(i, Action::Duplicate);
+            (copy, paste, duplicate)
+        });
+        if !(copy || duplicate || paste.is_some()) {
+            return;
+        }
+        let copied = self.copied_shape().map(|shape| {
+            let pos = shape
+                .outline()
+                .first()
+                .map(|point| *point.pos() + PASTE_OFFSET);
+            (Document::new(vec![shape], vec![]).to_json(), pos)
+        });
+        let pos = ctx.pointer_hover_pos();
+        match copied {
+            Some((Ok(json), copied_pos)) => {
+                if copy {
+                    self.status_message = "Copied to clipboard".to_string();
+                    ctx.copy_text(json.clone());
+                    self.copied_json = Some(json.clone());
+                }
+                if duplicate {
+                    if let Some(pos) = pos.or(copied_pos) {
+                        self.paste_shapes(&json, pos);
+                    }
+                }
+            }
+            Some((Err(e), _)) => self.status_message = e.to_string(),
+            None if copy || duplicate => {
+                self.status_message = "Select at least 2 vertices to copy".to_string()
+            }
+            None => {}
+        }
+        if let Some(text) = paste {
+            let pos = pos.unwrap_or(Pos2::ZERO + PASTE_OFFSET);
+            self.paste_shapes(&text, pos);
+        }
+    }
+
+    /// Smallest number of points the edited polygon (or path) can have
+    fn min_points_count(&self) -> usize {
+        if self.closed {
+            3
+        } else {
+            2
+        }
+    }
+
+    /// Changes the edited polygon into an open path, which starts and ends in given point
+    fn open_path_at(&mut self, point_index: usize) {
+        if !self.holes.is_empty() {
+            self.status_message = "Polygon with holes can't be opened".to_string();
+            return;
+        }
+        self.push_undo_step();
+        Point::open_at(&mut self.points, point_index);
+        self.closed = false;
+        self.spline_kind = None;
+        self.reset_selection();
+        self.status_message = "Polygon opened into a path".to_string();
+    }
+
+    /// Connects both ends of the edited path
+    fn close_path(&mut self) {
+        if self.points.len() < 3 {
+            self.status_message = "Path needs at least 3 points to be closed".to_string();
+            return;
+        }
+        self.push_undo_step();
+        Point::close(&mut self.points);
+        self.closed = true;
+        self.reset_selection();
+        self.status_message = "Path closed into a polygon".to_string();
+    }
+
+    /// Quick test rejecting positions too far from the polygon to hit any of its points or edges
+    fn is_near_polygon(&self, pos: Pos2) -> bool {
+        // Hull is not calculated before the first frame of editing, every position is tested then
+        self.convex_hull.is_empty()
+            || ConvexHull::contains(&self.convex_hull, pos, Self::HIT_TEST_MARGIN)
+    }
+
+    fn convexify(&mut self) {
+        self.push_undo_step();
+        let vertices_count = self.points.len();
+        self.points = ConvexHull::convexify(&self.points, self.flattening_tolerance);
+        self.reset_selection();
+        self.status_message = format!(
+            "Polygon replaced with its convex hull ({} -> {} vertices)",
+            vertices_count,
+            self.points.len()
+        );
+    }
+
+    /// Returns shape with given id, where 0 is the edited polygon and i > 0 is shapes[i - 1]
+    fn shape(&self, shape_id: usize) -> Shape {
+        match shape_id {
+            0 if self.closed => Shape::new(self.points.clone(), self.holes.clone()),
+            0 => Shape::new_open(self.points.clone()),
+            _ => self.shapes[shape_id - 1].clone(),
+        }
+    }
+
+    /// Returns all shapes, the edited one first
+    fn all_shapes(&self) -> Vec<Shape> {
+        (0..=self.shapes.len()).map(|id| self.shape(id)).collect()
+    }
+
+    /// Starts drawing a new polygon from scratch (point by point or freehand, depending on the mode),
+    /// current shapes are kept until drawing is finished
+    fn start_drawing_new_polygon(&mut self, polygon_mode: PolygonMode) {
+        let shapes_before_drawing = match self.polygon_mode {
+            PolygonMode::Editing => Some(self.all_shapes()),
+            _ => self.shapes_before_drawing.take(),
+        };
+        let editor = match polygon_mode {
+            PolygonMode::Freehand => Self::new_with_freehand_mode(),
+            _ => Self::new_with_drawing_mode(),
+        };
+        self.replace_keeping_bindings(editor);
+        self.shapes_before_drawing = shapes_before_drawing;
+    }
+
+    /// Replaces whole editor state, but keeps keys and mouse buttons chosen by user
+    fn replace_keeping_bindings(&mut self, editor: Self) {
+        let bindings = std::mem::take(&mut self.bindings);
+        let undo_history = std::mem::take(&mut self.undo_history);
+        *self = editor;
+        self.bindings = bindings;
+        self.undo_history = undo_history;
+    }
+
+    fn shape_name(shape_id: usize) -> String {
+        match shape_id {
+            0 => "Shape 1 (edited)".to_string(),
+            _ => format!("Shape {}", shape_id + 1),
+        }
+    }
+
+    fn reset_selection(&mut self) {
+        self.dragged_index = None;
+        self.control_point_dragged = None;
+        self.polygon_dragged_index = None;
+        self.selected_edge_start_index = None;
+        self.selected_point_index = None;
+        self.insert_after_index = None;
+        self.selected_run = None;
+        self.nudge_target = None;
+        self.keyboard_focus = None;
+        self.focus_menu = false;
+        self.boolean_operation_operands = (0, 0);
+    }
+
+    /// Removes the edited polygon and returns it as a shape
+    fn take_edited_shape(&mut self) -> Shape {
+        let edited = self.shape(0);
+        self.points.clear();
+        self.holes.clear();
+        edited
+    }
+
+    /// Makes the shape the edited polygon
+    fn set_edited_shape(&mut self, shape: Shape) {
+        self.closed = shape.is_closed();
+        let (outline, holes) = shape.into_parts();
+        self.points = outline;
+        self.holes = holes;
+        // Last point of an open path starts no edge, so it can't keep anything describing it.
+        // Splines are always closed, so an open path is edited as a polygon
+        if !self.closed {
+            self.spline_kind = None;
+            if let Some(last) = self.points.last_mut() {
+                last.remove_constraint();
+                last.remove_curve();
+            }
+        }
+    }
+
+    /// Makes shapes[shape_index] the edited polygon, the previously edited one becomes an ordinary shape
+    fn edit_shape(&mut self, shape_index: usize) {
+        self.push_undo_step();
+        let edited = self.take_edited_shape();
+        let shape = std::mem::replace(&mut self.shapes[shape_index], edited);
+        self.set_edited_shape(shape);
+        self.reset_selection();
+    }
+
+    /// Stores the edited polygon as an ordinary shape and starts drawing a new one
+    fn start_drawing_additional_polygon(&mut self) {
+        self.shapes_before_drawing = Some(self.all_shapes());
+        let edited = self.take_edited_shape();
+        self.shapes.push(edited);
+        self.polygon_mode = PolygonMode::Drawing;
+        self.reset_selection();
+    }
+
+    /// Replaces both operands with the result of the chosen boolean operation
+    fn apply_boolean_operation(&mut self) {
+        let (first, second) = self.boolean_operation_operands;
+        if !self.shape(first).is_closed() || !self.shape(second).is_closed() {
+            self.status_message = "Boolean operations need closed polygons".to_string();
+            return;
+        }
+        self.push_undo_step();
+        let result = Clipper::apply(
+            &self.shape(first),
+            &self.shape(second),
+            self.boolean_operation,
+            self.flattening_tolerance,
+        );
+        let result_count = result.len();
+
+        let mut operands = [first, second];
+        operands.sort_unstable();
+        for shape_id in operands.into_iter().rev().filter(|id| *id > 0) {
+            self.shapes.remove(shape_id - 1);
+        }
+        self.shapes.extend(result);
+
+        if first == 0 || second == 0 {
+            // Edited polygon was consumed, so one of the results (or any other shape) becomes the edited one
+            self.points.clear();
+            self.holes.clear();
+            if self.shapes.is_empty() {
+                self.polygon_mode = PolygonMode::Drawing;
+            } else {
+                let new_edited_index = self.shapes.len() - result_count.max(1);
+                let shape = self.shapes.remove(new_edited_index);
+                self.set_edited_shape(shape);
+            }
+        }
+        self.reset_selection();
+    }
+
+    fn show_shapes_controls(&mut self, ui: &mut egui::Ui) {
+        ui.label("Shapes");
+        ui.label(Self::shape_name(0));
+        let mut shape_to_edit = None;
+        let mut shape_to_remove = None;
+        for shape_index in 0..self.shapes.len() {
+            ui.horizontal(|ui| {
+                ui.label(Self::shape_name(shape_index + 1));
+                if ui.button("Edit").clicked() {
+                    shape_to_edit = Some(shape_index);
+                }
+                if ui.button("Remove").clicked() {
+                    shape_to_remove = Some(shape_index);
+                }
+            });
+        }
+        if let Some(shape_index) = shape_to_edit {
+            self.edit_shape(shape_index);
+        } else if let Some(shape_index) = shape_to_remove {
+            self.push_undo_step();
+            self.shapes.remove(shape_index);
+            self.boolean_operation_operands = (0, 0);
+        }
+        ui.vertical_centered(|ui| {
+            if ui.button("Add new polygon").clicked() {
+                self.start_drawing_additional_polygon();
+            }
+        });
+        ui.separator();
+
+        ui.label("Boolean operations");
+        let shapes_count = self.shapes.len() + 1;
+        let (first, second) = &mut self.boolean_operation_operands;
+        for (label, operand) in [("First", first), ("Second", second)] {
+            egui::ComboBox::from_label(label)
+                .selected_text(Self::shape_name(*operand))
+                .show_ui(ui, |ui| {
+                    for shape_id in 0..shapes_count {
+                        ui.selectable_value(operand, shape_id, Self::shape_name(shape_id));
+                    }
+                });
+        }
+        egui::ComboBox::from_label("Operation")
+            .selected_text(self.boolean_operation.name())
+            .show_ui(ui, |ui| {
+                for operation in BooleanOperation::ALL {
+                    ui.selectable_value(&mut self.boolean_operation, operation, operation.name());
+                }
+            });
+        ui.vertical_centered(|ui| {
+            let (first, second) = self.boolean_operation_operands;
+            if ui
+                .add_enabled(first != second, egui::Button::new("Apply operation"))
+                .clicked()
+            {
+                self.apply_boolean_operation();
+            }
+        });
+    }
+
+    fn show_offset_controls(&mut self, ui: &mut egui::Ui) {
+        ui.label("Offset");
+        ui.horizontal(|ui| {
+            ui.label("Distance");
+            ui.add(egui::DragValue::new(&mut self.offset_distance).speed(0.5));
+        });
+        egui::ComboBox::from_label("Join style")
+            .selected_text(self.offset_join_style.name())
+            .show_ui(ui, |ui| {
+                for join_style in JoinStyle::ALL {
+                    ui.selectable_value(&mut self.offset_join_style, join_style, join_style.name());
+                }
+            });
+        ui.add_enabled_ui(self.offset_join_style == JoinStyle::Miter, |ui| {
+            ui.horizontal(|ui| {
+                ui.label("Miter limit");
+                ui.add(
+                    egui::DragValue::new(&mut self.offset_miter_limit)
+                        .speed(0.1)
+                        .range(1.0..=f32::MAX),
+                );
+            });
+        });
+        ui.vertical_centered(|ui| {
+            if ui.button("Create offset polygon").clicked() {
+                let result = PolygonOffset::apply(
+                    &self.shape(0),
+                    self.offset_distance,
+                    self.offset_join_style,
+                    self.offset_miter_limit,
+                    self.flattening_tolerance,
+                );
+                if result.is_empty() {
+                    self.status_message = "Offset polygon would be empty".to_string();
+                } else {
+                    self.push_undo_step();
+                    self.shapes.extend(result);
+                }
+            }
+        });
+    }
+
+    fn show_simplify_controls(&mut self, ui: &mut egui::Ui) {
+        ui.label("Simplify");
+        ui.add(egui::Slider::new(&mut self.simplify_tolerance, 0.5..=50.0).text("Tolerance"));
+        ui.checkbox(&mut self.show_simplify_preview, "Show preview");
+        ui.vertical_centered(|ui| {
+            if ui.button("Simplify polygon").clicked() {
+                self.push_undo_step();
+                let vertices_count = self.points.len();
+                self.points = Simplification::simplify(&self.points, self.simplify_tolerance);
+                self.reset_selection();
+                self.status_message = format!(
+                    "Polygon simplified ({} -> {} vertices)",
+                    vertices_count,
+                    self.points.len()
+                );
+            }
+        });
+    }
+
+    fn show_corners_controls(&mut self, ui: &mut egui::Ui) {
+        ui.label("Corners");
+        ui.horizontal(|ui| {
+            ui.label("Radius");
+            ui.add(
+                egui::DragValue::new(&mut self.fillet_radius)
+                    .speed(0.5)
+                    .range(1.0..=f32::MAX),
+            );
+        });
+        ui.vertical_centered(|ui| {
+            if ui.button("Round all corners").clicked() {
+                let shapes_before_edit = self.all_shapes();
+                let rounded = Corners::fillet_all(&mut self.points, self.fillet_radius);
+                if rounded > 0 {
+                    self.push_undo_state(shapes_before_edit);
+                }
+                self.reset_selection();
+                self.status_message = format!("Rounded {} corners", rounded);
+            }
+        });
+    }
+
+    fn show_spline_controls(&mut self, ui: &mut egui::Ui) {
+        ui.label("Spline");
+        ui.radio_value(&mut self.spline_kind, None, "Polygon");
+        ui.radio_value(
+            &mut self.spline_kind,
+            Some(SplineKind::BSpline),
+            SplineKind::BSpline.name(),
+        );
+        ui.radio_value(
+            &mut self.spline_kind,
+            Some(SplineKind::CatmullRom),
+            SplineKind::CatmullRom.name(),
+        );
+        ui.vertical_centered(|ui| {
+            if ui
+                .add_enabled(
+                    self.spline_kind.is_some(),
+                    egui::Button::new("Convert into bezier segments"),
+                )
+                .clicked()
+            {
+                self.convert_spline();
+            }
+        });
+    }
+
+    fn show_smoothing_controls(&mut self, ui: &mut egui::Ui) {
+        ui.label("Smoothing");
+        ui.add(egui::Slider::new(&mut self.smoothing_tension, 0.0..=1.0).text("Tension"));
+        ui.vertical_centered(|ui| {
+            if ui.button("Smooth all").clicked() {
+                self.push_undo_step();
+                let smoothed = Spline::smooth(&self.points, self.smoothing_tension);
+                // Constrained edges (including curves of constant length) are left as they are
+                let smoothed_count = self
+                    .points
+                    .iter()
+                    .zip(&smoothed)
+                    .filter(|(before, after)| before.segment() != after.segment())
+                    .count();
+                self.points = smoothed;
+                self.reset_selection();
+                self.status_message = format!(
+                    "Smoothed {} edges, {} edges were kept",
+                    smoothed_count,
+                    self.points.len() - smoothed_count
+                );
+            }
+        });
+    }
+
+    /// Replaces the edited polygon with bezier segments describing the current spline
+    fn convert_spline(&mut self) {
+        let Some(kind) = self.spline_kind.take().filter(|_| self.closed) else {
+            return;
+        };
+        self.push_undo_step();
+        self.points = Spline::to_bezier_polygon(&self.points, kind);
+        self.reset_selection();
+        self.status_message = format!(
+            "{} converted into {} bezier segments, constraints were removed",
+            kind.name(),
+            self.points.len()
+        );
+    }
+
+    fn show_curvature_controls(&mut self, ui: &mut egui::Ui) {
+        ui.label("Curvature");
+        ui.checkbox(&mut self.show_curvature_comb, "Show curvature comb");
+        ui.add(
+            egui::Slider::new(&mut self.curvature_comb_scale, 10.0..=100000.0)
+                .logarithmic(true)
+                .text("Scale"),
+        );
+    }
+
+    fn show_positioning_controls(&mut self, ui: &mut egui::Ui) {
+        ui.label("Precise positioning");
+        ui.checkbox(&mut self.snap_to_grid, "Snap to grid");
+        ui.add(egui::Slider::new(&mut self.grid_step, 5.0..=100.0).text("Grid step"));
+        ui.vertical_centered(|ui| {
+            if ui
+                .add_enabled(!self.undo_history.is_empty(), egui::Button::new("Undo"))
+                .clicked()
+            {
+                self.undo();
+            }
+        });
+    }
+
+    fn save_document(&mut self) {
+        let shapes = self.all_shapes();
+        let triangles = if self.export_triangulation && self.closed {
+            Triangulation::triangulate(&shapes[0], self.flattening_tolerance)
+        } else {
+            vec![]
+        };
+        self.status_message =
+            match Document::new(shapes, triangles).save(Path::new(&self.document_path)) {
+                Ok(()) => format!("Saved document to {}", self.document_path),
+                Err(e) => e.to_string(),
+            };
+    }
+
+    fn load_document(&mut self) {
+        let mut shapes = match Document::load(Path::new(&self.document_path)) {
+            Ok(document) => document.into_shapes(),
+            Err(e) => {
+                self.status_message = e.to_string();
+                return;
+            }
+        };
+        if shapes.is_empty() || !shapes.iter().all(Shape::is_valid) {
+            self.status_message = "Invalid document: every polygon needs at least 3 points and every open path at least 2".to_string();
+            return;
+        }
+        match self.polygon_mode {
+            PolygonMode::Editing => self.push_undo_step(),
+            _ => self.record_drawing(),
+        }
+        let edited = shapes.remove(0);
+        self.set_edited_shape(edited);
+        self.shapes = shapes;
+        self.polygon_mode = PolygonMode::Editing;
+        self.reset_selection();
+        self.status_message = format!("Loaded document from {}", self.document_path);
+    }
+
+    fn show_document_controls(&mut self, ui: &mut egui::Ui) {
+        ui.label("Document");
+        ui.text_edit_singleline(&mut self.document_path);
+        ui.checkbox(&mut self.export_triangulation, "Save triangulation");
+        ui.horizontal(|ui| {
+            if ui
+                .add_enabled(
+                    self.polygon_mode == PolygonMode::Editing,
+                    egui::Button::new("Save"),
+                )
+                .clicked()
+            {
+                self.save_document();
+            }
+            if ui.button("Load").clicked() {
+                self.load_document();
+            }
+        });
+        if !self.status_message.is_empty() {
+            ui.label(&self.status_message);
+        }
+    }
+
+    /// Triggers actions bound to keys, which work on the selected edge or point or start tools
+    pub fn handle_shortcuts(&mut self, ctx: &egui::Context) {
+        // Keys typed into text fields are not meant for shortcuts
+        if ctx.wants_keyboard_input() {
+            return;
+        }
+        let pressed: Vec<Action> = ctx.input(|i| {
+            Action::ALL
+                .into_iter()
+                .filter(|action| {
+                    action.mode() == Mode::Editing
+                        && !action.is_mouse_gesture()
+                        && self.bindings.pressed(i, *action)
+                })
+                .collect()
+        });
+        for action in pressed {
+            let edge = self.selected_edge_start_index;
+            let point = self.selected_point_index;
+            // Open menu gets Tab and Enter for moving between its buttons and clicking them
+            let menu_open = edge.is_some() || point.is_some();
+            match (action, edge, point) {
+                (Action::MakeHorizontal, Some(id), _) => {
+                    self.constrain_edge(id, EdgeConstraint::Horizontal)
+                }
+                (Action::MakeVertical, Some(id), _) => {
+                    self.constrain_edge(id, EdgeConstraint::Vertical)
+                }
+                (Action::MakeConstantWidth, Some(id), _) => {
+                    let id_next = Point::get_next_index(&self.points, id);
+                    let width = self.points[id].pos().distance(*self.points[id_next].pos());
+                    self.constrain_edge(id, EdgeConstraint::ConstWidth(width.round() as i32));
+                }
+                (Action::ApplyG0, _, Some(id)) => self.apply_continuity(id, ContinuityType::G0),
+                (Action::ApplyC1, _, Some(id)) => self.apply_continuity(id, ContinuityType::C1),
+                (Action::ApplyG1, _, Some(id)) => self.apply_continuity(id, ContinuityType::G1),
+                (Action::MakeHorizontal | Action::MakeVertical | Action::MakeConstantWidth, ..) => {
+                    self.status_message =
+                        "Select an edge with RMB or open its menu from keyboard first".to_string()
+                }
+                (Action::ApplyG0 | Action::ApplyC1 | Action::ApplyG1, ..) => {
+                    self.status_message =
+                        "Select a point with RMB or open its menu from keyboard first".to_string()
+                }
+                (Action::DrawNewPolygon, ..) => {
+                    self.start_drawing_new_polygon(PolygonMode::Drawing)
+                }
+                (Action::DrawFreehand, ..) => self.start_drawing_new_polygon(PolygonMode::Freehand),
+                (Action::ShowShortcuts, ..) => self.show_shortcuts_window = true,
+                (Action::Undo, ..) => self.undo(),
+                (
+                    Action::FocusNext
+                    | Action::FocusPrevious
+                    | Action::SwitchFocus
+                    | Action::OpenFocusedMenu,
+                    ..,
+                ) if menu_open => {}
+                (Action::FocusNext, ..) => self.move_keyboard_focus(ctx, true),
+                (Action::FocusPrevious, ..) => self.move_keyboard_focus(ctx, false),
+                (Action::SwitchFocus, ..) => self.switch_keyboard_focus(),
+                (Action::OpenFocusedMenu, ..) => self.open_menu_of_keyboard_focus(),
+                (Action::CloseMenu, ..) => {
+                    self.selected_edge_start_index = None;
+                    self.selected_point_index = None;
+                    self.keyboard_focus = None;
+                    self.focus_menu = false;
+                }
+                _ => {}
+            }
+        }
+    }
+
+    /// Focuses the next (or previous) vertex or edge, depending on which kind is focused now.
+    /// Vertices are focused first.
+    fn move_keyboard_focus(&mut self, ctx: &egui::Context, forward: bool) {
+        // Tab also moves focus between widgets of the panel, which would then take Enter and arrows
+        ctx.memory_mut(|memory| {
+            if let Some(id) = memory.focused() {
+                memory.surrender_focus(id);
+            }
+        });
+        let step = |id: usize, count: usize| {
+            if forward {
+                (id + 1) % count
+            } else {
+                (id + count - 1) % count
+            }
+        };
+        let vertices_count = self.points.len();
+        let edges_count = Point::edges_count(&self.points, self.closed);
+        self.keyboard_focus = Some(match self.keyboard_focus {
+            Some(KeyboardFocus::Vertex(id)) => KeyboardFocus::Vertex(step(id, vertices_count)),
+            Some(KeyboardFocus::Edge(id)) => KeyboardFocus::Edge(step(id, edges_count)),
+            None if forward => KeyboardFocus::Vertex(0),
+            None => KeyboardFocus::Vertex(vertices_count - 1),
+        });
+        self.describe_keyboard_focus();
+    }
+
+    /// Focuses the edge starting in the focused vertex, or the first vertex of the focused edge
+    fn switch_keyboard_focus(&mut self) {
+        let edges_count = Point::edges_count(&self.points, self.closed);
+        self.keyboard_focus = Some(match self.keyboard_focus {
+            // Last point of an open path starts no edge, so the edge ending in it is focused
+            Some(KeyboardFocus::Vertex(id)) => KeyboardFocus::Edge(id.min(edges_count - 1)),
+            Some(KeyboardFocus::Edge(id)) => KeyboardFocus::Vertex(id),
+            None => KeyboardFocus::Edge(0),
+        });
+        self.describe_keyboard_focus();
+    }
+
+    /// Tells which element is focused, arrow keys move focused vertex
+    fn describe_keyboard_focus(&mut self) {
+        match self.keyboard_focus {
+            Some(KeyboardFocus::Vertex(id)) => {
+                self.nudge_target = Some(NudgeTarget::Vertex(id));
+                self.status_message = format!("Vertex {} of {}", id + 1, self.points.len());
+            }
+            Some(KeyboardFocus::Edge(id)) => {
+                self.status_message = format!(
+                    "Edge {} of {}",
+                    id + 1,
+                    Point::edges_count(&self.points, self.closed)
+                );
+            }
+            None => {}
+        }
+    }
+
+    fn open_menu_of_keyboard_focus(&mut self) {
+        let vertices_count = self.points.len();
+        let edges_count = Point::edges_count(&self.points, self.closed);
+        match self.keyboard_focus {
+            Some(KeyboardFocus::Vertex(id)) if id < vertices_count => {
+                self.selected_point_index = Some(id);
+                self.selected_edge_start_index = None;
+                self.focus_menu = true;
+            }
+            Some(KeyboardFocus::Edge(id)) if id < edges_count => {
+                self.selected_edge_start_index = Some(id);
+                self.selected_point_index = None;
+                self.focus_menu = true;
+            }
+            _ => self.status_message = "Focus a vertex or edge with Tab first".to_string(),
+        }
+    }
+
+    /// Position of the marker of the element focused with keyboard, None if it no longer exists
+    fn keyboard_focus_pos(&self) -> Option<Pos2> {
+        match self.keyboard_focus? {
+            KeyboardFocus::Vertex(id) => self.points.get(id).map(|point| *point.pos()),
+            KeyboardFocus::Edge(id) if id < Point::edges_count(&self.points, self.closed) => {
+                Some(Point::get_middle_point(
+                    &self.points[id],
+                    &self.points[Point::get_next_index(&self.points, id)],
+                ))
+            }
+            KeyboardFocus::Edge(_) => None,
+        }
+    }
+
+    /// Binds the next pressed key (or mouse button, for mouse gestures) to the action chosen
+    /// in shortcuts window. Escape cancels rebinding.
+    pub fn handle_rebinding(&mut self, ctx: &egui::Context) {
+        let Some(action) = self.rebound_action else {
+            return;
+        };
+        let trigger = ctx.input(|i| {
+            i.events
+                .iter()
+                .find_map(|event| Trigger::from_event(event, i.modifiers))
+        });
+        match trigger {
+            Some(Trigger::Key(_, egui::Key::Escape)) => self.rebound_action = None,
+            Some(trigger @ Trigger::Key(..)) if !action.is_mouse_gesture() => {
+                self.bind(action, trigger)
+            }
+            Some(trigger @ Trigger::Mouse(..)) if action.is_mouse_gesture() => {
+                self.bind(action, trigger)
+            }
+            _ => {}
+        }
+    }
+
+    fn bind(&mut self, action: Action, trigger: Trigger) {
+        self.rebound_action = None;
+        self.status_message = match self.bindings.bind(action, trigger) {
+            Some(swapped) => format!(
+                "{} bound to {}, {} now uses {}",
+                action.name(),
+                trigger.name(),
+                swapped.name(),
+                self.bindings.trigger(swapped).name()
+            ),
+            None => format!("{} bound to {}", action.name(), trigger.name()),
+        };
+    }
+
+    /// Shows all actions with their triggers, created from the bindings so that it's always up to date
+    pub fn show_shortcuts(&mut self, ctx: &egui::Context) {
+        let mut open = self.show_shortcuts_window;
+        Window::new("Shortcuts").open(&mut open).show(ctx, |ui| {
+            for mode in Mode::ALL {
+                ui.strong(mode.name());
+                egui::Grid::new(("shortcuts_grid", mode.name()))
+                .striped(true)
+                .show(ui, |ui| {
+                    for action in Action::ALL.into_iter().filter(|action| action.mode() == mode) {
+                        ui.label(action.name());
+                        if self.rebound_action == Some(action) {
+                            let hint = if action.is_mouse_gesture() {
+                                "Click mouse button..."
+                            } else {
+                                "Press key..."
+                            };
+                            ui.label(hint);
+                        } else {
+                            ui.label(self.bindings.trigger(action).name());
+                        }
+                        if ui.button("Change").clicked() {
+                            self.rebound_action = Some(action);
+                        }
+                        ui.end_row();
+                    }
+                });
+            }
+            ui.separator();
+            ui.label("Moving keys move the point or control point grabbed last by 1 px, with Shift held by 10 px and by grid step when snapping to grid.");
+            ui.label("Keys of each mode are separate, the same key can work differently while drawing and editing. Shortcuts are remembered when the app is closed.");
+            ui.label("Modifiers held while clicking or pressing become part of the shortcut. Press Escape to cancel changing.");
+            if ui.button("Restore default shortcuts").clicked() {
+                self.bindings = Bindings::default();
+                self.rebound_action = None;
+            }
+        });
+        self.show_shortcuts_window = open;
+        if !open {
+            self.rebound_action = None;
+        }
+    }
+
     pub fn show_tutorial(&mut self, ctx: &egui::Context) {
         if self.show_tutorial_window {
             Window::new("Tutorial")
@@ -488,11 +2230,35 @@ impl PolygonEditor {
                     ui.label("2. To add new point or apply any change to edge click RMB on the edge and choose proper option.");
                     ui.label("3. To remove constraint from edge click RMB on the edge with constraint and choose \"Remove Constraint\"");
                     ui.label("4. To apply continuity to the point click RMB on the point and choose proper continuity (default is C1).");
-                    ui.label("5. To remove bezier segment click RMB on the point that is the start of it and choose \"Remove bezier segment\"");
+                    ui.label("5. To remove bezier segment (or any other curve) click RMB on the point that is the start of it and choose \"Remove bezier segment\", or click RMB on the curve and choose \"Change into line\"");
                     ui.label("6. To move whole polygon hold ctrl + LMB and drag any point.");
                     ui.label("7. To remove point click alt + LMB on it.");
                     ui.label("8. To create new polygon click \"Draw new Polygon\" and click LMB in the next positions where point should be placed. Polygon will be created when there are at least 2 points and you click on the first point");
                     ui.label("9. To restore the polygon that comes up when app is run click \"Restore default state\"");
+                    ui.label("10. Points where edges cross each other are marked with red circles. Check \"Prevent self-intersections\" to refuse moves that would make the polygon self-intersecting.");
+                    ui.label("11. To work with more shapes click \"Add new polygon\". Only one shape is edited at a time, click \"Edit\" next to any other shape to edit it instead.");
+                    ui.label("12. To combine two shapes choose them in \"Boolean operations\" section, pick the operation and click \"Apply operation\". Both shapes are replaced with the result.");
+                    ui.label("13. To create outline in constant distance from the edited polygon set the distance (negative for inset) and join style in \"Offset\" section and click \"Create offset polygon\". It is added as a new shape.");
+                    ui.label("14. To see triangulation of the edited polygon check \"Show triangulation\".");
+                    ui.label("15. To save all shapes to a file (or load them from it) enter the path in \"Document\" section and click \"Save\" (or \"Load\"). Check \"Save triangulation\" to save triangles covering the edited polygon too.");
+                    ui.label("16. To see convex hull of the edited polygon check \"Show convex hull\". To replace the polygon with its hull click \"Convexify\", edges that lie on the hull keep their constraints.");
+                    ui.label("17. To remove redundant vertices set the tolerance in \"Simplify\" section and click \"Simplify polygon\". Check \"Show preview\" to see the result first. Vertices of edges with constraints or bezier segments are always kept.");
+                    ui.label("18. To round or cut off a corner between two straight edges click RMB on the point and choose \"Round corner\" (enter radius) or \"Cut off corner\" (enter length of the new edge). To round all corners at once set the radius in \"Corners\" section and click \"Round all corners\".");
+                    ui.label("19. Edges can also be changed into quadratic bezier segments (with single control point) or circular arcs. To change the arc drag the point in its middle. Continuity works the same way for all kinds of curves, for arcs only direction of the tangent can be kept, so C1 behaves like G1 there.");
+                    ui.label("20. Point between two cubic bezier segments can also have C2 (equal second derivatives) or G2 (equal curvature) continuity. Keeping them moves also the second control points of the segments.");
+                    ui.label("21. Smoothness of the curves can be checked with curvature comb (Curvature section). Normals scaled by curvature are drawn along the curves, and difference of curvatures on both sides is written next to each joint.");
+                    ui.label("22. Curve can have constant length (RMB on the curve -> \"Make constant length\"). Length is measured along the curve, and when any end of it is moved, the other one slides along the line connecting them to keep it.");
+                    ui.label("23. In \"Spline\" section the polygon can be shown as closed B-spline (vertices are its control points) or Catmull-Rom spline (passing through all vertices). \"Convert into bezier segments\" replaces the polygon with bezier segments describing the same curve.");
+                    ui.label("24. \"Smooth all\" (Smoothing section) changes every edge without constraint into bezier segment with C1 continuity in all points. Tangents come from cardinal spline, tension 0 gives the roundest shape and tension 1 keeps the edges straight.");
+                    ui.label("25. \"Draw freehand\" lets you draw the polygon with a single stroke (hold LMB and drag). After releasing the button, the stroke is closed and fitted with bezier segments with G1 continuity, not further from it than the fitting tolerance. Escape cancels drawing and brings back the previous shapes.");
+                    ui.label("26. While drawing a new polygon, press LMB and drag to pull out tangent handles of the placed point. Edge from the previous point becomes a bezier segment with C1 continuity in the new point (G1 when Alt is held, then the incoming handle keeps a third of the edge length). Simple click places a corner.");
+                    ui.label("27. Polygon can also be an open path. To finish drawing without closing the polygon click the last placed point again. To open a closed polygon click RMB on a point and choose \"Open path here\", the path then starts and ends in that point. To close it again click \"Close path\" (in the panel or in the menu of its end), ends in the same place are merged. Open paths have no continuity in their ends and can't be used for operations that need an area (offset, simplification, corners, smoothing, splines, boolean operations, triangulation).");
+                    ui.label("28. While drawing, Backspace removes the last placed point, Escape cancels drawing and goes back to the previous shapes and Enter closes the polygon. Line from the last point to the cursor shows length and angle of the next edge, hold Shift to keep its angle a multiple of 45 degrees.");
+                    ui.label("29. To add more vertices after a point click RMB on it and choose \"Insert vertices after here\". Each next LMB click inserts a new point between the last inserted one and the successor of the chosen point. Constraint and curve of the split edge are removed. Press Enter or Escape to finish.");
+                    ui.label("30. To select a run of vertices click shift + LMB on its first and then on its last point. Ctrl + C copies the run (or the whole edited polygon when nothing is selected) to the clipboard as JSON document, ctrl + V pastes shapes from the clipboard at the cursor as new shapes and ctrl + D duplicates the selection there. Constraints, curves and continuity are kept, runs are pasted as open paths.");
+                    ui.label("31. All mouse gestures (dragging, removing and selecting points, opening menus) and keyboard shortcuts can be changed in \"Shortcuts\" window (F1). Keys work on the edge or point selected with RMB: H, V and W make the edge horizontal, vertical or constant width, 0, 1 and G apply G0, C1 or G1 in the point. N and F start drawing new polygon or freehand. Keys and gestures of drawing mode (Backspace, Escape, Enter, Alt while pulling handles), clipboard shortcuts and arrows can be changed too, and chosen shortcuts are remembered when the app is closed.");
+                    ui.label("32. Arrow keys move the point or control point grabbed or selected last (marked with a square) by 1 px, with Shift held by 10 px. With \"Snap to grid\" on, dragged points land on the grid and arrows move them by grid step. Constraints and continuity are kept. Ctrl + Z undoes the last edit of any kind, where all nudges in a row count as one.");
+                    ui.label("33. Polygon can be edited without the mouse. Tab and Shift + Tab go through vertices (or edges), E switches between vertex and edge focus, Enter opens menu of the focused element, where Tab or arrows move between buttons and Enter clicks them, and Escape closes it. Focused vertex can be moved with arrow keys.");
                     ui.separator();
                     ui.label("LMB - left mouse button, RMB - right mouse button.");
                 });
@@ -506,8 +2272,11 @@ impl PolygonEditor {
                 .show(ctx, |ui| {
                     ui.label("1. Application stores points as vector of points. Each edge is just points[i]-points[i+1] (with special case of points[n-1]-points[0]). When edge is needed (for example for selecting it with RMB or to check if edge has any constraint, it's identified by its first point, meaning if we want to know what costraint edge [i]-[i+1] has, we need to check point [i].");
                     ui.label("2. When any point is moved, app iterates over all points in both directions (meaning it goes i, i+1,...i-1 and i, i-1,..., i+1. For each edge it checks if edge has any constraint and if so it properly moved other points so that every constraint is still satisfied.");
-                    ui.label("3. In case of bezier segment, it works very similiar to simple edge, e.g. when bezier segment is defined on edge [i]-[i+1], then control points are stored inside point [i].");
-                    ui.label("4. Constraints that are caused by continuity in points adjacent to bezier segments are checked in same iteration in which edge constraints are checked. After any point is moved, its adjacent control points are checked and if C1 or G1 is applied then they are properly moved to hold these constraints.");
+                    ui.label("3. In case of bezier segment, it works very similiar to simple edge, e.g. when bezier segment is defined on edge [i]-[i+1], then control points are stored inside point [i]. The same goes for quadratic bezier segments and arcs, each kind of edge is a variant of EdgeSegment.");
+                    ui.label("4. Constraints that are caused by continuity in points adjacent to bezier segments are checked in same iteration in which edge constraints are checked. After any point is moved, its adjacent control points are checked and if C1 or G1 is applied then they are properly moved to hold these constraints. Each kind of segment has handles at both ends (end of the edge for lines, control point for bezier segments, point on the tangent for arcs) and factor by which the handle vector has to be multiplied to get the derivative (1 for lines and arcs, 2 for quadratic and 3 for cubic bezier segments), which allows mixing them.");
+                    ui.label("5. Boolean operations flatten bezier segments of both shapes, split all edges in points where they meet any other edge and keep only pieces that have result of the operation on exactly one side. These pieces are then linked back into outlines and holes.");
+                    ui.label("6. Curves are drawn, hit-tested, filled and exported as polylines. Each curve (as cubic pieces) is recursively split in half with de Casteljau algorithm until its inner control points are closer to the chord than the tolerance set in the panel, so flat parts get few points and strongly bent ones get many.");
+                    ui.label("7. Open paths use the same vector of points, but the last point starts no edge (it's always a line without constraint). Walking over edges after a move stops in the ends of the path instead of going around, and no continuity is kept in them.");
                 });
         }
     }
@@ -519,22 +2288,37 @@ impl Default for PolygonEditor {
             // Point::new(Pos2::new(50.0, 50.0)),
             // Point::new(Pos2::new(100.0, 50.0)),
             // Point::new(Pos2::new(75.0, 100.0)),
-            Point::new_all(Pos2::new(118.8, 359.2), None, None, ContinuityType::C1),
+            Point::new_all(
+                Pos2::new(118.8, 359.2),
+                None,
+                EdgeSegment::Line,
+                ContinuityType::C1,
+            ),
             Point::new_all(
                 Pos2::new(132.2, 439.5),
                 None,
-                Some(BezierData::new([
+                EdgeSegment::CubicBezier(BezierData::new([
                     Pos2::new(173.2, 493.4),
                     Pos2::new(221.1, 492.4),
                 ])),
                 ContinuityType::G0,
             ),
-            Point::new_all(Pos2::new(244.6, 449.1), None, None, ContinuityType::C1),
-            Point::new_all(Pos2::new(314.9, 319.0), None, None, ContinuityType::C1),
+            Point::new_all(
+                Pos2::new(244.6, 449.1),
+                None,
+                EdgeSegment::Line,
+                ContinuityType::C1,
+            ),
+            Point::new_all(
+                Pos2::new(314.9, 319.0),
+                None,
+                EdgeSegment::Line,
+                ContinuityType::C1,
+            ),
             Point::new_all(
                 Pos2::new(362.4, 449.2),
                 None,
-                Some(BezierData::new([
+                EdgeSegment::CubicBezier(BezierData::new([
                     Pos2::new(378.3, 492.7),
                     Pos2::new(430.5, 502.1),
                 ])),
@@ -543,7 +2327,7 @@ impl Default for PolygonEditor {
             Point::new_all(
                 Pos2::new(481.5, 451.3),
                 None,
-                Some(BezierData::new([
+                EdgeSegment::CubicBezier(BezierData::new([
                     Pos2::new(519.4, 413.6),
                     Pos2::new(425.0, 419.1),
                 ])),
@@ -552,38 +2336,43 @@ impl Default for PolygonEditor {
             Point::new_all(
                 Pos2::new(425.0, 361.5),
                 Some(EdgeConstraint::Vertical),
-                None,
+                EdgeSegment::Line,
                 ContinuityType::G1,
             ),
             Point::new_all(
                 Pos2::new(425.0, 259.1),
                 Some(EdgeConstraint::ConstWidth(123)),
-                None,
+                EdgeSegment::Line,
                 ContinuityType::C1,
             ),
             Point::new_all(
                 Pos2::new(524.6, 187.0),
                 Some(EdgeConstraint::ConstWidth(107)),
-                None,
+                EdgeSegment::Line,
                 ContinuityType::C1,
             ),
             Point::new_all(
                 Pos2::new(494.6, 84.3),
                 Some(EdgeConstraint::Horizontal),
-                None,
+                EdgeSegment::Line,
                 ContinuityType::C1,
             ),
             Point::new_all(
                 Pos2::new(318.8, 84.3),
                 Some(EdgeConstraint::Vertical),
+                EdgeSegment::Line,
+                ContinuityType::C1,
+            ),
+            Point::new_all(
+                Pos2::new(318.8, 239.0),
                 None,
+                EdgeSegment::Line,
                 ContinuityType::C1,
             ),
-            Point::new_all(Pos2::new(318.8, 239.0), None, None, ContinuityType::C1),
             Point::new_all(
                 Pos2::new(261.8, 89.8),
                 None,
-                Some(BezierData::new([
+                EdgeSegment::CubicBezier(BezierData::new([
                     Pos2::new(242.8, 40.0),
                     Pos2::new(102.6, 81.6),
                 ])),
@@ -592,7 +2381,7 @@ impl Default for PolygonEditor {
             Point::new_all(
                 Pos2::new(145.4, 104.4),
                 None,
-                Some(BezierData::new([
+                EdgeSegment::CubicBezier(BezierData::new([
                     Pos2::new(188.2, 127.3),
                     Pos2::new(125.8, 217.3),
                 ])),
@@ -601,20 +2390,25 @@ impl Default for PolygonEditor {
             Point::new_all(
                 Pos2::new(153.1, 217.3),
                 Some(EdgeConstraint::Horizontal),
+                EdgeSegment::Line,
+                ContinuityType::C1,
+            ),
+            Point::new_all(
+                Pos2::new(235.2, 217.3),
                 None,
+                EdgeSegment::Line,
                 ContinuityType::C1,
             ),
-            Point::new_all(Pos2::new(235.2, 217.3), None, None, ContinuityType::C1),
             Point::new_all(
                 Pos2::new(110.8, 294.7),
                 Some(EdgeConstraint::ConstWidth(79)),
-                None,
+                EdgeSegment::Line,
                 ContinuityType::C1,
             ),
             Point::new_all(
                 Pos2::new(182.0, 329.0),
                 Some(EdgeConstraint::ConstWidth(70)),
-                None,
+                EdgeSegment::Line,
                 ContinuityType::C1,
             ),
         ];
@@ -622,12 +2416,57 @@ impl Default for PolygonEditor {
             polygon_mode: PolygonMode::Editing,
             line_drawing_algorithm: LineDrawingAlgorithm::Bresenham,
             points,
+            closed: true,
+            holes: vec![],
+            shapes: vec![],
+            boolean_operation_operands: (0, 0),
+            boolean_operation: BooleanOperation::Union,
+            offset_distance: 20.0,
+            offset_join_style: JoinStyle::Miter,
+            offset_miter_limit: 4.0,
+            simplify_tolerance: 5.0,
+            show_simplify_preview: false,
+            fillet_radius: 20.0,
+            chamfer_length: 20.0,
+            flattening_tolerance: 0.25,
+            smoothing_tension: 0.0,
+            pen_handle: None,
+            pen_first_handle: None,
+            pen_dragging: false,
+            shapes_before_drawing: None,
+            freehand_stroke: vec![],
+            freehand_tolerance: 4.0,
+            spline_kind: None,
+            show_curvature_comb: false,
+            curvature_comb_scale: 1000.0,
             dragged_index: None,
-            bezier_control_point_dragged: None,
+            control_point_dragged: None,
             polygon_dragged_index: None,
             selected_edge_start_index: None,
             selected_point_index: None,
+            selected_run: None,
+            copied_json: None,
+            nudge_target: None,
+            keyboard_focus: None,
+            focus_menu: false,
+            undo_history: vec![],
+            nudging: false,
+            snap_to_grid: false,
+            grid_step: 20.0,
+            insert_after_index: None,
             popups: Popups::default(),
+            self_intersections: vec![],
+            prevent_self_intersections: false,
+            convex_hull: vec![],
+            show_convex_hull: false,
+            show_triangulation: false,
+            triangles: vec![],
+            document_path: "polygon.json".to_string(),
+            export_triangulation: false,
+            status_message: String::new(),
+            bindings: Bindings::default(),
+            rebound_action: None,
+            show_shortcuts_window: false,
             show_tutorial_window: false,
             show_implementation_window: false,
         }
@@ -635,6 +2474,10 @@ impl Default for PolygonEditor {
 }
 
 impl eframe::App for PolygonEditor {
+    fn save(&mut self, storage: &mut dyn eframe::Storage) {
+        eframe::set_value(storage, Self::BINDINGS_STORAGE_KEY, &self.bindings);
+    }
+
     fn update(&mut self, ctx: &egui::Context, _frame: &mut eframe::Frame) {
         egui::SidePanel::right("right_panel")
             .resizable(false)
@@ -657,25 +2500,109 @@ impl eframe::App for PolygonEditor {
                     LineDrawingAlgorithm::Bultin,
                     "Builtin Algorithm",
                 );
+                ui.add(
+                    egui::Slider::new(&mut self.flattening_tolerance, 0.05..=5.0)
+                        .logarithmic(true)
+                        .text("Curve tolerance"),
+                );
+                ui.separator();
+                if self.polygon_mode == PolygonMode::Editing {
+                    if self.self_intersections.is_empty() {
+                        ui.colored_label(Color32::LIGHT_GREEN, "Polygon is simple");
+                    } else {
+                        ui.colored_label(
+                            Color32::RED,
+                            format!(
+                                "Polygon is not simple ({} crossings)",
+                                self.self_intersections.len()
+                            ),
+                        );
+                    }
+                    let perimeter = Point::perimeter(&self.points, self.closed);
+                    if self.closed {
+                        ui.label(format!("Perimeter: {:.1}", perimeter));
+                    } else {
+                        ui.label(format!("Open path, length: {:.1}", perimeter));
+                        ui.vertical_centered(|ui| {
+                            if ui.button("Close path").clicked() {
+                                self.close_path();
+                            }
+                        });
+                    }
+                }
+                ui.checkbox(
+                    &mut self.prevent_self_intersections,
+                    "Prevent self-intersections",
+                );
+                ui.checkbox(&mut self.show_triangulation, "Show triangulation");
+                ui.checkbox(&mut self.show_convex_hull, "Show convex hull");
+                if self.polygon_mode == PolygonMode::Editing {
+                    ui.vertical_centered(|ui| {
+                        if ui
+                            .add_enabled(self.closed, egui::Button::new("Convexify"))
+                            .clicked()
+                        {
+                            self.convexify();
+                        }
+                    });
+                }
+                ui.separator();
+                self.show_document_controls(ui);
                 ui.separator();
                 ui.vertical_centered(|ui| {
                     if ui.button("Draw new polygon").clicked() {
-                        *self = Self::new_with_drawing_mode();
+                        self.start_drawing_new_polygon(PolygonMode::Drawing);
+                    }
+                    if ui.button("Draw freehand").clicked() {
+                        self.start_drawing_new_polygon(PolygonMode::Freehand);
                     }
                 });
+                if self.polygon_mode == PolygonMode::Freehand {
+                    ui.add(
+                        egui::Slider::new(&mut self.freehand_tolerance, 0.5..=20.0)
+                            .text("Fitting tolerance"),
+                    );
+                }
                 ui.separator();
                 ui.vertical_centered(|ui| {
                     if ui.button("Restore default state").clicked() {
-                        *self = Self::default();
+                        self.replace_keeping_bindings(Self::default());
                     }
                 });
                 ui.separator();
+                if self.polygon_mode == PolygonMode::Editing {
+                    self.show_shapes_controls(ui);
+                    ui.separator();
+                    // These operations work on the whole outline, so they need it closed
+                    ui.add_enabled_ui(self.closed, |ui| {
+                        self.show_offset_controls(ui);
+                        ui.separator();
+                        self.show_simplify_controls(ui);
+                        ui.separator();
+                        self.show_corners_controls(ui);
+                        ui.separator();
+                        self.show_smoothing_controls(ui);
+                        ui.separator();
+                        self.show_spline_controls(ui);
+                    });
+                    ui.separator();
+                    self.show_curvature_controls(ui);
+                    ui.separator();
+                    self.show_positioning_controls(ui);
+                    ui.separator();
+                }
                 ui.vertical_centered(|ui| {
                     if ui.button("Tutorial").clicked() {
                         self.show_tutorial_window = true;
                     }
                 });
                 ui.separator();
+                ui.vertical_centered(|ui| {
+                    if ui.button("Shortcuts").clicked() {
+                        self.show_shortcuts_window = true;
+                    }
+                });
+                ui.separator();
                 ui.vertical_centered(|ui| {
                     if ui.button("Implementation").clicked() {
                         self.show_implementation_window = true;
@@ -686,6 +2613,27 @@ impl eframe::App for PolygonEditor {
 
         egui::CentralPanel::default().show(ctx, |ui| {
             let painter = ui.painter();
+            for shape in &self.shapes {
+                for ring in std::iter::once(shape.outline()).chain(shape.holes().iter()) {
+                    match self.line_drawing_algorithm {
+                        LineDrawingAlgorithm::Bultin => Drawer::draw_outline_builtin(
+                            ring,
+                            shape.is_closed(),
+                            painter,
+                            Color32::GRAY,
+                            1.0,
+                            self.flattening_tolerance,
+                        ),
+                        LineDrawingAlgorithm::Bresenham => Drawer::draw_outline_bresenham(
+                            ring,
+                            shape.is_closed(),
+                            painter,
+                            Color32::GRAY,
+                            self.flattening_tolerance,
+                        ),
+                    }
+                }
+            }
             match self.polygon_mode {
                 PolygonMode::Drawing => {
                     // Important: Order here matters!
@@ -695,17 +2643,41 @@ impl eframe::App for PolygonEditor {
                             painter,
                             Color32::LIGHT_GREEN,
                             1.0,
+                            self.flattening_tolerance,
                         ),
                         LineDrawingAlgorithm::Bresenham => {
                             Drawer::draw_incomplete_polygon_bresenham(
                                 &self.points,
                                 painter,
                                 Color32::YELLOW,
+                                self.flattening_tolerance,
                             )
                         }
                     };
+                    if let Some(handle) = self.pen_handle {
+                        Drawer::draw_handles(
+                            *self.points.last().unwrap().pos(),
+                            &[handle],
+                            painter,
+                        );
+                    }
+                    if let Some(handle) = self.pen_first_handle {
+                        Drawer::draw_handles(*self.points[0].pos(), &[handle], painter);
+                    }
+                    if let (Some(last), Some(pos)) = (self.points.last(), ctx.pointer_hover_pos()) {
+                        if !self.pen_dragging && pos.x <= ui.min_rect().width() {
+                            let snap = ctx.input(|i| i.modifiers.shift);
+                            Drawer::draw_rubber_band(
+                                *last.pos(),
+                                self.next_drawn_position(pos, snap),
+                                painter,
+                                Color32::GRAY,
+                            );
+                        }
+                    }
                     Drawer::draw_points(
                         &self.points,
+                        false,
                         None,
                         painter,
                         Color32::DARK_BLUE,
@@ -713,49 +2685,248 @@ impl eframe::App for PolygonEditor {
                     );
                     // LMB on plane
                     self.handle_adding_point_in_drawing_mode(ctx, ui.min_rect().width());
+                    // Backspace, Escape, Enter
+                    self.handle_keys_in_drawing_mode(ctx);
+                }
+                PolygonMode::Freehand => {
+                    Drawer::draw_stroke(&self.freehand_stroke, painter, Color32::YELLOW);
+                    // LMB dragged on plane
+                    self.handle_drawing_freehand_stroke(ctx, ui.min_rect().width());
                 }
                 PolygonMode::Editing => {
+                    if self.snap_to_grid {
+                        Drawer::draw_grid(
+                            painter.clip_rect(),
+                            self.grid_step,
+                            painter,
+                            Color32::from_gray(40),
+                        );
+                    }
+                    self.self_intersections = SelfIntersections::find(&self.points, self.closed);
+                    self.convex_hull = ConvexHull::of_polygon(&self.points);
+                    if self.show_convex_hull {
+                        Drawer::draw_convex_hull(
+                            &self.convex_hull,
+                            painter,
+                            Color32::from_rgb(120, 200, 220),
+                        );
+                    }
+                    if self.show_triangulation && self.closed {
+                        self.triangles =
+                            Triangulation::triangulate(&self.shape(0), self.flattening_tolerance);
+                        Drawer::draw_triangles(
+                            &self.triangles,
+                            painter,
+                            Color32::from_rgb(70, 90, 160),
+                        );
+                    }
                     // Important: Order here matters!
-                    match self.line_drawing_algorithm {
-                        LineDrawingAlgorithm::Bultin => Drawer::draw_polygon_builtin(
+                    match (
+                        self.spline_kind.filter(|_| self.closed),
+                        &self.line_drawing_algorithm,
+                    ) {
+                        (Some(kind), _) => Drawer::draw_spline(
+                            &Spline::to_cubics(&self.points, kind),
+                            &self.points,
+                            painter,
+                            Color32::YELLOW,
+                            self.flattening_tolerance,
+                        ),
+                        (None, LineDrawingAlgorithm::Bultin) => Drawer::draw_polygon_builtin(
                             &self.points,
+                            self.closed,
                             self.selected_point_index,
                             self.selected_edge_start_index,
                             painter,
                             Color32::LIGHT_GREEN,
                             Color32::ORANGE,
                             1.0,
+                            self.flattening_tolerance,
                         ),
-                        LineDrawingAlgorithm::Bresenham => Drawer::draw_polygon_bresenham(
+                        (None, LineDrawingAlgorithm::Bresenham) => Drawer::draw_polygon_bresenham(
                             &self.points,
+                            self.closed,
                             self.selected_point_index,
                             self.selected_edge_start_index,
                             painter,
                             Color32::YELLOW,
                             Color32::ORANGE,
+                            self.flattening_tolerance,
                         ),
                     };
+                    for hole in &self.holes {
+                        match self.line_drawing_algorithm {
+                            LineDrawingAlgorithm::Bultin => Drawer::draw_outline_builtin(
+                                hole,
+                                true,
+                                painter,
+                                Color32::LIGHT_GREEN,
+                                1.0,
+                                self.flattening_tolerance,
+                            ),
+                            LineDrawingAlgorithm::Bresenham => Drawer::draw_outline_bresenham(
+                                hole,
+                                true,
+                                painter,
+                                Color32::YELLOW,
+                                self.flattening_tolerance,
+                            ),
+                        }
+                    }
+                    if self.show_simplify_preview && self.closed {
+                        let simplified =
+                            Simplification::simplify(&self.points, self.simplify_tolerance);
+                        match self.line_drawing_algorithm {
+                            LineDrawingAlgorithm::Bultin => Drawer::draw_outline_builtin(
+                                &simplified,
+                                true,
+                                painter,
+                                Color32::LIGHT_BLUE,
+                                1.0,
+                                self.flattening_tolerance,
+                            ),
+                            LineDrawingAlgorithm::Bresenham => Drawer::draw_outline_bresenham(
+                                &simplified,
+                                true,
+                                painter,
+                                Color32::LIGHT_BLUE,
+                                self.flattening_tolerance,
+                            ),
+                        }
+                    }
+                    if self.show_curvature_comb {
+                        Drawer::draw_curvature_comb(
+                            &CurvatureComb::of_polygon(&self.points, self.curvature_comb_scale),
+                            painter,
+                            Color32::from_rgb(200, 120, 220),
+                        );
+                        Drawer::draw_curvature_jumps(
+                            &CurvatureComb::joint_jumps(&self.points, self.closed),
+                            painter,
+                            Color32::from_rgb(200, 120, 220),
+                        );
+                    }
                     Drawer::draw_points(
                         &self.points,
+                        self.closed,
                         self.selected_point_index,
                         painter,
                         Color32::DARK_BLUE,
                         Color32::DARK_GREEN,
                     );
-                    // ctrl + LMB on point
-                    self.handle_dragging_polygon(ctx);
-                    // alt + LMB on point
-                    self.handle_removing_point(ctx);
-                    // LMB on point
-                    self.handle_dragging_points(ctx);
-                    // RMB on edge/point
-                    self.handle_selecting_edge_or_point(ctx);
-                    self.show_context_menu_for_selected_edge(ctx, ui);
-                    self.show_context_menu_for_selected_point(ctx);
+                    Drawer::draw_intersections(&self.self_intersections, painter);
+                    if let Some((start, end)) = self.selected_run {
+                        Drawer::draw_selected_run(
+                            &self.points,
+                            start,
+                            end,
+                            painter,
+                            Color32::LIGHT_RED,
+                        );
+                    }
+                    if let Some(pos) = self.nudge_target.and_then(|t| self.nudge_target_pos(t)) {
+                        Drawer::draw_square_marker(pos, painter, Color32::WHITE);
+                    }
+                    if let Some(pos) = self.keyboard_focus_pos() {
+                        Drawer::draw_square_marker(pos, painter, Color32::LIGHT_BLUE);
+                    }
+                    if let Some(insert_after_index) = self.insert_after_index {
+                        if let Some(pos) = ctx.pointer_hover_pos() {
+                            Drawer::draw_rubber_band(
+                                *self.points[insert_after_index].pos(),
+                                pos,
+                                painter,
+                                Color32::GRAY,
+                            );
+                        }
+                        // LMB on plane
+                        self.handle_inserting_vertices(ctx, ui.min_rect().width());
+                    } else if self.rebound_action.is_some() {
+                        // Key or mouse button chosen for an action in shortcuts window
+                        self.handle_rebinding(ctx);
+                    } else {
+                        // Default mouse triggers are written next to the handlers, all of them can be changed
+                        // ctrl + LMB on point
+                        self.handle_dragging_polygon(ctx);
+                        // alt + LMB on point
+                        self.handle_removing_point(ctx);
+                        // LMB on point
+                        self.handle_dragging_points(ctx);
+                        // shift + LMB on point
+                        self.handle_selecting_run(ctx);
+                        // ctrl + C, ctrl + V, ctrl + D
+                        self.handle_clipboard(ctx);
+                        // RMB on edge/point
+                        self.handle_selecting_edge_or_point(ctx);
+                        // Keys bound to actions
+                        self.handle_shortcuts(ctx);
+                        // Arrows
+                        self.handle_nudging(ctx);
+                        self.show_context_menu_for_selected_edge(ctx, ui);
+                        self.show_context_menu_for_selected_point(ctx);
+                    }
                     self.show_tutorial(ctx);
+                    self.show_shortcuts(ctx);
                     self.show_implementation(ctx);
                 }
             }
         });
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn positions(points: &[Point]) -> Vec<Pos2> {
+        points.iter().map(|p| *p.pos()).collect()
+    }
+
+    #[test]
+    fn cancelling_freehand_drawing_restores_shapes() {
+        let mut editor = PolygonEditor::default();
+        let outline = positions(&editor.points);
+
+        editor.start_drawing_new_polygon(PolygonMode::Freehand);
+        assert!(editor.polygon_mode == PolygonMode::Freehand);
+        assert!(editor.points.is_empty());
+
+        editor.cancel_drawing();
+        assert!(editor.polygon_mode == PolygonMode::Editing);
+        assert_eq!(positions(&editor.points), outline);
+        assert!(editor.closed);
+    }
+
+    #[test]
+    fn finished_freehand_stroke_can_be_undone() {
+        let mut editor = PolygonEditor::default();
+        let outline = positions(&editor.points);
+
+        editor.start_drawing_new_polygon(PolygonMode::Freehand);
+        editor.freehand_stroke = (0..64)
+            .map(|i| {
+                let angle = i as f32 / 64.0 * std::f32::consts::TAU;
+                Pos2::new(300.0, 300.0) + Vec2::angled(angle) * 100.0
+            })
+            .collect();
+        editor.finish_freehand_stroke();
+        assert!(editor.polygon_mode == PolygonMode::Editing);
+        assert_ne!(positions(&editor.points), outline);
+
+        editor.undo();
+        assert_eq!(positions(&editor.points), outline);
+    }
+
+    #[test]
+    fn editing_open_path_turns_spline_off() {
+        let mut editor = PolygonEditor {
+            spline_kind: Some(SplineKind::CatmullRom),
+            ..PolygonEditor::default()
+        };
+        let path = Shape::new_open(editor.points.clone());
+
+        editor.set_edited_shape(path);
+        assert!(!editor.closed);
+        assert!(editor.spline_kind.is_none());
+    }
+}
@@ -3,6 +3,18 @@ pub struct Popups {
     const_width_constraint_popup_id: egui::Id,
     const_width_constraint_user_input: i32,
     const_width_constraint_submitted: bool,
+    // Const Length Constraint popup fields
+    const_length_constraint_popup_id: egui::Id,
+    const_length_constraint_user_input: i32,
+    const_length_constraint_submitted: bool,
+    // Fillet popup fields
+    fillet_popup_id: egui::Id,
+    fillet_radius_user_input: f32,
+    fillet_submitted: bool,
+    // Chamfer popup fields
+    chamfer_popup_id: egui::Id,
+    chamfer_length_user_input: f32,
+    chamfer_submitted: bool,
 }
 
 impl Popups {
@@ -51,6 +63,138 @@ impl Popups {
     pub fn reset_const_width_constraint_submitted(&mut self) {
         self.const_width_constraint_submitted = false;
     }
+
+    pub fn open_const_length_constraint_popup_below_widget(
+        &mut self,
+        ui: &mut egui::Ui,
+        intial_length: i32,
+    ) {
+        ui.memory_mut(|mem| mem.toggle_popup(self.const_length_constraint_popup_id));
+        self.const_length_constraint_user_input = intial_length;
+    }
+
+    pub fn render_const_length_constraint_popup_below_widget(
+        &mut self,
+        ui: &mut egui::Ui,
+        widget: &egui::Response,
+    ) {
+        egui::popup_below_widget(
+            ui,
+            self.const_length_constraint_popup_id,
+            widget,
+            egui::PopupCloseBehavior::CloseOnClickOutside,
+            |ui| {
+                ui.horizontal(|ui| {
+                    ui.label("Enter length");
+                    ui.add(egui::DragValue::new(
+                        &mut self.const_length_constraint_user_input,
+                    ))
+                });
+                if ui.button("Apply").clicked() {
+                    ui.memory_mut(|mem| mem.toggle_popup(self.const_length_constraint_popup_id));
+                    self.const_length_constraint_submitted = true;
+                }
+            },
+        );
+    }
+
+    pub fn const_length_constraint_submitted(&self) -> bool {
+        self.const_length_constraint_submitted
+    }
+
+    pub fn const_length_constraint_user_input(&self) -> i32 {
+        self.const_length_constraint_user_input
+    }
+
+    pub fn reset_const_length_constraint_submitted(&mut self) {
+        self.const_length_constraint_submitted = false;
+    }
+
+    pub fn open_fillet_popup_below_widget(&mut self, ui: &mut egui::Ui, initial_radius: f32) {
+        ui.memory_mut(|mem| mem.toggle_popup(self.fillet_popup_id));
+        self.fillet_radius_user_input = initial_radius;
+    }
+
+    pub fn render_fillet_popup_below_widget(&mut self, ui: &mut egui::Ui, widget: &egui::Response) {
+        self.fillet_submitted |= Self::render_length_popup_below_widget(
+            ui,
+            widget,
+            self.fillet_popup_id,
+            "Enter radius",
+            &mut self.fillet_radius_user_input,
+        );
+    }
+
+    pub fn fillet_submitted(&self) -> bool {
+        self.fillet_submitted
+    }
+
+    pub fn fillet_radius_user_input(&self) -> f32 {
+        self.fillet_radius_user_input
+    }
+
+    pub fn reset_fillet_submitted(&mut self) {
+        self.fillet_submitted = false;
+    }
+
+    pub fn open_chamfer_popup_below_widget(&mut self, ui: &mut egui::Ui, initial_length: f32) {
+        ui.memory_mut(|mem| mem.toggle_popup(self.chamfer_popup_id));
+        self.chamfer_length_user_input = initial_length;
+    }
+
+    pub fn render_chamfer_popup_below_widget(
+        &mut self,
+        ui: &mut egui::Ui,
+        widget: &egui::Response,
+    ) {
+        self.chamfer_submitted |= Self::render_length_popup_below_widget(
+            ui,
+            widget,
+            self.chamfer_popup_id,
+            "Enter length",
+            &mut self.chamfer_length_user_input,
+        );
+    }
+
+    pub fn chamfer_submitted(&self) -> bool {
+        self.chamfer_submitted
+    }
+
+    pub fn chamfer_length_user_input(&self) -> f32 {
+        self.chamfer_length_user_input
+    }
+
+    pub fn reset_chamfer_submitted(&mut self) {
+        self.chamfer_submitted = false;
+    }
+
+    /// Renders popup with single positive value input, returns whether it was submitted
+    fn render_length_popup_below_widget(
+        ui: &mut egui::Ui,
+        widget: &egui::Response,
+        popup_id: egui::Id,
+        label: &str,
+        user_input: &mut f32,
+    ) -> bool {
+        let mut submitted = false;
+        egui::popup_below_widget(
+            ui,
+            popup_id,
+            widget,
+            egui::PopupCloseBehavior::CloseOnClickOutside,
+            |ui| {
+                ui.horizontal(|ui| {
+                    ui.label(label);
+                    ui.add(egui::DragValue::new(user_input).range(1.0..=f32::MAX))
+                });
+                if ui.button("Apply").clicked() {
+                    ui.memory_mut(|mem| mem.toggle_popup(popup_id));
+                    submitted = true;
+                }
+            },
+        );
+        submitted
+    }
 }
 
 impl Default for Popups {
@@ -59,6 +203,15 @@ impl Default for Popups {
             const_width_constraint_popup_id: "const_width_constraint_popup_id".into(),
             const_width_constraint_user_input: 0,
             const_width_constraint_submitted: false,
+            const_length_constraint_popup_id: "const_length_constraint_popup_id".into(),
+            const_length_constraint_user_input: 0,
+            const_length_constraint_submitted: false,
+            fillet_popup_id: "fillet_popup_id".into(),
+            fillet_radius_user_input: 0.0,
+            fillet_submitted: false,
+            chamfer_popup_id: "chamfer_popup_id".into(),
+            chamfer_length_user_input: 0.0,
+            chamfer_submitted: false,
         }
     }
 }